@@ -1,133 +1,510 @@
-use crate::ui::{Cpuview, Overview, ProcessesView, Tab};
-use std::{io::Stdout, rc::Rc};
-use sysinfo::{System, SystemExt};
-use termion::{event::Key, raw::RawTerminal};
+use crate::config::Config;
+use crate::core::{Key, MetricsHistory, Snapshot, SnapshotRequest};
+use crate::keymap::{Action, KeyHint, Keymap};
+use crate::ui::{
+    centered_rect, BatteryView, Cpuview, DisplaySettings, MemoryView, NetworkView, Overview,
+    PressureView, ProcessesView, SensorsView, StorageView, Tab, TableSort, TableSortPredicate,
+    View, ViewContext,
+};
+use std::time::Instant;
+use sysinfo::{Pid, PidExt};
 use tui::{
-    backend::TermionBackend,
-    layout::{Constraint, Layout},
-    style::{Color, Modifier, Style},
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
     text::{Span, Spans},
-    widgets::Tabs,
+    widgets::{Block, Borders, Clear, Paragraph, Tabs},
     Frame,
 };
 
-const TAB_TITLES: [&str; 6] = [
-    "Overview",
-    "CPU",
-    "Memory",
-    "Processes",
-    "Storage",
-    "Network",
-];
-
-
 pub(crate) struct App {
+    /// Visible tabs, in order
+    tabs: Vec<Tab>,
     active_tab: Tab,
-    system_info: Rc<System>,
+    keymap: Keymap,
+    /// Units, thresholds and colours the views draw with
+    settings: DisplaySettings,
+    /// Scroll offset of the help popup, None while it is closed
+    help: Option<u16>,
+    /// Why the last change of the configuration file was rejected
+    config_error: Option<String>,
+    /// The process sort of the configuration. A reload applies it only when it changed,
+    /// so the column picked with the keys stays.
+    process_sort: Option<(TableSortPredicate, TableSort)>,
+    /// The latest snapshot published by the collector thread
+    snapshot: Snapshot,
+    /// Past values of the metrics, shared by the charts of every view
+    history: MetricsHistory,
     overview: Overview,
     cpu_view: Cpuview,
+    memory_view: MemoryView,
+    network_view: NetworkView,
     process_view: ProcessesView,
+    storage_view: StorageView,
+    sensors_view: SensorsView,
+    battery_view: BatteryView,
+    pressure_view: PressureView,
 }
 
 impl App {
-    pub(crate) fn new() -> Self {
-        let mut system_info = System::new_all();
-        system_info.refresh_all();
-        let system_info = Rc::new(system_info);
-
-        App {
-            active_tab: Tab::Overview,
-            system_info,
+    pub(crate) fn new(config: &Config) -> Self {
+        let mut app = App {
+            tabs: Vec::new(),
+            active_tab: config.start_tab(),
+            keymap: Keymap::default(),
+            settings: DisplaySettings::default(),
+            help: None,
+            config_error: None,
+            process_sort: None,
+            snapshot: Snapshot::default(),
+            history: MetricsHistory::new(config.history_config()),
             overview: Overview::new(),
             cpu_view: Cpuview::new(),
+            memory_view: MemoryView::new(),
+            network_view: NetworkView::new(),
             process_view: ProcessesView::new(),
+            storage_view: StorageView::new(),
+            sensors_view: SensorsView::new(),
+            battery_view: BatteryView::new(),
+            pressure_view: PressureView::new(),
+        };
+        app.configure(config);
+        app
+    }
+
+    /// Applies the settings of the configuration, at start and whenever the file changes
+    pub(crate) fn configure(&mut self, config: &Config) {
+        self.tabs = config.tabs.clone();
+        if !self.tabs.contains(&self.active_tab) {
+            self.active_tab = config.start_tab();
         }
+        self.keymap = config.keymap();
+        self.settings = config.display_settings();
+        self.config_error = None;
+        self.history.set_config(config.history_config());
+        self.cpu_view.set_windows(config.cpu_windows());
+
+        let processes = &config.processes;
+        self.process_view.set_columns(processes.columns.clone());
+        let process_sort = (processes.sort, processes.order);
+        if self.process_sort != Some(process_sort) {
+            self.process_view.sort_by(processes.sort, processes.order);
+            self.process_sort = Some(process_sort);
+        }
+    }
+
+    /// Lists only the processes given on the command line
+    pub(crate) fn filter_processes(&mut self, pids: &[u32], user: Option<String>) {
+        self.process_view
+            .set_pid_filter(pids.iter().copied().map(Pid::from_u32).collect());
+        self.process_view.set_user_filter(user);
     }
 
-    pub(crate) fn render(&mut self, frame: &mut Frame<TermionBackend<RawTerminal<Stdout>>>) {
+    pub(crate) fn show_config_error(&mut self, error: String) {
+        self.config_error = Some(error);
+    }
+
+    /// Returns the action bound to `key`
+    pub(crate) fn action(&self, key: Key) -> Option<Action> {
+        self.keymap.action(key)
+    }
+
+    pub(crate) fn render<B: Backend>(&mut self, frame: &mut Frame<B>) {
         let layout = Layout::default()
             .direction(tui::layout::Direction::Vertical)
-            .constraints([Constraint::Percentage(5), Constraint::Percentage(95)].as_ref())
+            .constraints(
+                [
+                    Constraint::Percentage(5),
+                    Constraint::Min(0),
+                    // the key legend
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
             .split(frame.size());
 
-        match self.active_tab {
-            Tab::Overview => self.overview.render_overview(frame, layout[1]),
-            Tab::CPU => self
-                .cpu_view
-                .render_cpu(frame, layout[1], &self.system_info),
-            Tab::Processes => {
-                self.process_view
-                    .render_processes(frame, layout[1], &self.system_info)
-            }
-            /*Tab::Memory => render_memory(),
-            Tab::Storage => render_storage(),
-            Tab::Network => render_network()*/
-            _ => (),
-        }
+        let context = ViewContext {
+            snapshot: &self.snapshot,
+            history: &self.history,
+            keymap: &self.keymap,
+            settings: &self.settings,
+        };
+        let view: &mut dyn View<B> = match self.active_tab {
+            Tab::Overview => &mut self.overview,
+            Tab::CPU => &mut self.cpu_view,
+            Tab::Memory => &mut self.memory_view,
+            Tab::Processes => &mut self.process_view,
+            Tab::Storage => &mut self.storage_view,
+            Tab::Network => &mut self.network_view,
+            Tab::Sensors => &mut self.sensors_view,
+            Tab::Battery => &mut self.battery_view,
+            Tab::Pressure => &mut self.pressure_view,
+        };
+        view.render(frame, layout[1], &context);
+        let view_actions = view.actions();
+        let view_hints = view.key_hints();
 
-        let tab_menu = TAB_TITLES
-            .iter()
-            .map(|tab| {
-                let (first, rest) = tab.split_at(1);
-                Spans::from(vec![
-                    Span::styled(
-                        first,
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::UNDERLINED),
-                    ),
-                    Span::styled(rest, Style::default().fg(Color::White)),
-                ])
-            })
-            .collect();
+        let theme = self.settings.theme;
+        let tab_menu =
+            self.tabs
+                .iter()
+                .map(|tab| {
+                    let title = tab.title();
+                    // the letter which switches to the tab is underlined, if the title has it
+                    let hotkeys: Vec<char> = self
+                        .keymap
+                        .keys(Action::switch_to(*tab))
+                        .into_iter()
+                        .filter_map(|key| match key {
+                            Key::Char(ch) => Some(ch),
+                            _ => None,
+                        })
+                        .collect();
+                    let (before, key, after) = match title.char_indices().find(|(_, ch)| {
+                        hotkeys.iter().any(|hotkey| ch.eq_ignore_ascii_case(hotkey))
+                    }) {
+                        Some((index, ch)) => (
+                            &title[..index],
+                            &title[index..index + ch.len_utf8()],
+                            &title[index + ch.len_utf8()..],
+                        ),
+                        None => (title, "", ""),
+                    };
+                    Spans::from(vec![
+                        Span::styled(before, Style::default().fg(theme.text)),
+                        Span::styled(
+                            key,
+                            Style::default()
+                                .fg(theme.highlight)
+                                .add_modifier(Modifier::UNDERLINED),
+                        ),
+                        Span::styled(after, Style::default().fg(theme.text)),
+                    ])
+                })
+                .collect();
 
+        let selected = self
+            .tabs
+            .iter()
+            .position(|tab| *tab == self.active_tab)
+            .unwrap_or(0);
         let tabs = Tabs::new(tab_menu)
-            .select(self.active_tab.into())
+            .select(selected)
             //.block(Block::default().title("Menu").borders(Borders::ALL))
-            .style(Style::default().fg(Color::White))
-            .highlight_style(Style::default().fg(Color::Yellow))
+            .style(Style::default().fg(theme.text))
+            .highlight_style(Style::default().fg(theme.highlight))
             .divider(Span::raw("|"));
 
-        frame.render_widget(tabs, layout[0]);
+        match &self.config_error {
+            Some(error) => {
+                let top_layout = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Min(0), Constraint::Percentage(50)].as_ref())
+                    .split(layout[0]);
+                let error = Paragraph::new(error.as_str())
+                    .style(Style::default().fg(theme.error))
+                    .alignment(Alignment::Right);
+                frame.render_widget(tabs, top_layout[0]);
+                frame.render_widget(error, top_layout[1]);
+            }
+            None => frame.render_widget(tabs, layout[0]),
+        }
+
+        let hints = if self.help.is_some() {
+            vec![KeyHint::Key("↑↓", "scroll"), KeyHint::Key("Esc", "close")]
+        } else if self.is_modal() {
+            view_hints
+        } else {
+            let mut hints = vec![
+                KeyHint::Action(Action::Help, "help"),
+                KeyHint::Action(Action::Quit, "quit"),
+                KeyHint::Action(Action::NextTab, "next tab"),
+            ];
+            hints.extend(view_hints);
+            hints
+        };
+        self.render_key_legend(frame, layout[2], &hints);
+
+        if self.help.is_some() {
+            self.render_help(frame, layout[1], view_actions);
+        }
+    }
+
+    /// Draws the keys of the legend with what they do, the actions without a key are left out
+    fn render_key_legend<B: Backend>(&self, frame: &mut Frame<B>, area: Rect, hints: &[KeyHint]) {
+        let theme = self.settings.theme;
+        let mut spans = Vec::new();
+        for (key, description) in hints.iter().filter_map(|hint| self.keymap.hint(*hint)) {
+            if !spans.is_empty() {
+                spans.push(Span::raw("  "));
+            }
+            spans.push(Span::styled(
+                key,
+                Style::default()
+                    .fg(theme.highlight)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                format!(" {}", description),
+                Style::default().fg(theme.text),
+            ));
+        }
+        frame.render_widget(Paragraph::new(Spans::from(spans)), area);
+    }
+
+    /// Draws the popup listing the global bindings and those of the active view
+    fn render_help<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        view_actions: &[(Action, &'static str)],
+    ) {
+        let global_actions: Vec<(Action, &'static str)> = [
+            Action::Quit,
+            Action::Help,
+            Action::NextTab,
+            Action::PreviousTab,
+        ]
+        .into_iter()
+        .chain(self.tabs.iter().map(|tab| Action::switch_to(*tab)))
+        .map(|action| (action, action.description()))
+        .collect();
+        // the actions without a key are left out
+        let bound = |actions: &[(Action, &'static str)]| -> Vec<(String, &'static str)> {
+            actions
+                .iter()
+                .filter_map(|(action, description)| {
+                    let keys = self.keymap.keys(*action);
+                    if keys.is_empty() {
+                        return None;
+                    }
+                    let names: Vec<String> = keys.iter().map(Key::to_string).collect();
+                    Some((names.join(", "), *description))
+                })
+                .collect()
+        };
+        let global_bindings = bound(&global_actions);
+        let view_bindings = bound(view_actions);
+        let key_width = global_bindings
+            .iter()
+            .chain(&view_bindings)
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let theme = self.settings.theme;
+        let section = |title: &str| {
+            Spans::from(Span::styled(
+                title.to_string(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+        };
+        let binding = |(keys, description): &(String, &str)| {
+            Spans::from(vec![
+                Span::styled(
+                    format!("  {:<width$}  ", keys, width = key_width),
+                    Style::default().fg(theme.highlight),
+                ),
+                Span::raw(description.to_string()),
+            ])
+        };
+        let mut lines = vec![section("Global")];
+        lines.extend(global_bindings.iter().map(binding));
+        lines.push(Spans::default());
+        lines.push(section(self.active_tab.title()));
+        if view_bindings.is_empty() {
+            lines.push(Spans::from("  no keys of its own"));
+        }
+        lines.extend(view_bindings.iter().map(binding));
+
+        // borders take 2 lines
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let max_scroll = (lines.len() as u16 + 2).saturating_sub(height);
+        let scroll = self.help.unwrap_or(0).min(max_scroll);
+        self.help = Some(scroll);
+
+        let popup_area = centered_rect(60, height, area);
+        let help = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Help - {}", self.active_tab.title())),
+            )
+            .scroll((scroll, 0));
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(help, popup_area);
     }
 
-    pub(crate) fn handle_arrow_keys(&self, key: Key) {
+    pub(crate) fn handle_arrow_keys(&mut self, key: Key) {
         match self.active_tab {
             Tab::Overview => (),
             Tab::CPU => (),
             Tab::Memory => (),
             Tab::Processes => self.process_view.handle_arrow_keys(key),
             Tab::Storage => (),
-            Tab::Network => (),
+            Tab::Network => self.network_view.handle_arrow_keys(key),
+            Tab::Sensors => self.sensors_view.handle_arrow_keys(key),
+            Tab::Battery => (),
+            Tab::Pressure => self.pressure_view.handle_arrow_keys(key),
         }
     }
 
-    pub(crate) fn switch_tab(&mut self, ch: char) {
-        match ch {
-            'o' | 'O' => self.active_tab = Tab::Overview,
-            'c' | 'C' => self.active_tab = Tab::CPU,
-            'm' | 'M' => self.active_tab = Tab::Memory,
-            'p' | 'P' => self.active_tab = Tab::Processes,
-            's' | 'S' => self.active_tab = Tab::Storage,
-            'n' | 'N' => self.active_tab = Tab::Network,
-            '\t' => self.active_tab.next(),
-            _ => (),
+    /// Returns true while the help or a popup of the active view is open, they receive every key press
+    pub(crate) fn is_modal(&self) -> bool {
+        if self.help.is_some() {
+            return true;
+        }
+        match self.active_tab {
+            Tab::Processes => self.process_view.is_modal(),
+            _ => false,
         }
     }
 
-    pub(crate) fn previous_tab(&mut self) {
-        self.active_tab.previous();
+    /// The keys bound to the navigation actions also move through the popups,
+    /// except while the filter prompt takes the typed characters
+    pub(crate) fn handle_modal_key(&mut self, key: Key) {
+        if let Some(scroll) = self.help {
+            self.help = match self.keymap.navigation_key(key) {
+                _ if self.keymap.action(key) == Some(Action::Help) => None,
+                Key::Esc | Key::Char('\n') | Key::Char('q') => None,
+                Key::Up => Some(scroll.saturating_sub(1)),
+                Key::Down => Some(scroll.saturating_add(1)),
+                Key::PageUp => Some(scroll.saturating_sub(10)),
+                Key::PageDown => Some(scroll.saturating_add(10)),
+                Key::Home => Some(0),
+                // the rendering stops at the last line
+                Key::End => Some(u16::MAX),
+                _ => Some(scroll),
+            };
+            return;
+        }
+
+        if let Tab::Processes = self.active_tab {
+            let key = if self.process_view.is_filtering() {
+                key
+            } else {
+                self.keymap.navigation_key(key)
+            };
+            self.process_view.handle_modal_key(key);
+        }
     }
 
-    pub(crate) fn tick(&mut self) {
-        match self.active_tab {
-            Tab::Overview => {
-                self.overview.update();
+    /// Switches tabs or lets the active view handle the action
+    pub(crate) fn handle_action(&mut self, action: Action) {
+        if let Some(tab) = action.tab() {
+            // hidden tabs cannot be switched to
+            if self.tabs.contains(&tab) {
+                self.active_tab = tab;
             }
-            Tab::CPU => {
-                self.cpu_view.update();
+            return;
+        }
+
+        match action {
+            Action::Quit => (),
+            Action::Help => self.help = Some(0),
+            Action::NextTab => self.move_tab(1),
+            Action::PreviousTab => self.previous_tab(),
+            Action::Cancel => self.handle_escape(),
+            Action::CycleWindow => {
+                if let Tab::CPU = self.active_tab {
+                    self.cpu_view.cycle_window();
+                }
             }
-            Tab::Memory | Tab::Processes | Tab::Storage | Tab::Network  => {}
+            Action::Up
+            | Action::Down
+            | Action::Left
+            | Action::Right
+            | Action::PageUp
+            | Action::PageDown
+            | Action::Home
+            | Action::End => {
+                if let Some(key) = action.navigation_key() {
+                    self.handle_arrow_keys(key);
+                }
+            }
+            _ => {
+                if let Tab::Processes = self.active_tab {
+                    self.process_view.handle_action(action);
+                }
+            }
+        }
+    }
+
+    fn handle_escape(&mut self) {
+        if let Tab::Processes = self.active_tab {
+            self.process_view.handle_escape();
         }
     }
+
+    fn previous_tab(&mut self) {
+        self.move_tab(self.tabs.len() - 1);
+    }
+
+    /// Moves `steps` visible tabs forward, wrapping around after the last one
+    fn move_tab(&mut self, steps: usize) {
+        let position = self
+            .tabs
+            .iter()
+            .position(|tab| *tab == self.active_tab)
+            .unwrap_or(0);
+        self.active_tab = self.tabs[(position + steps) % self.tabs.len()];
+    }
+
+    /// Takes a snapshot from the collector thread and updates the views with it
+    pub(crate) fn update(&mut self, snapshot: Snapshot) {
+        self.snapshot = snapshot;
+        self.history
+            .follow_cgroup(self.pressure_view.selected_cgroup());
+        self.history.record(&self.snapshot, Instant::now());
+        let snapshot = &self.snapshot;
+        let history = &self.history;
+
+        self.overview.update(snapshot);
+        self.cpu_view.update(&snapshot.cpu);
+        self.memory_view.update(&snapshot.memory);
+        self.storage_view.update(snapshot, history);
+        self.network_view.update(&snapshot.networks, history);
+
+        if let Tab::Processes = self.active_tab {
+            self.process_view.update(snapshot);
+        }
+    }
+
+    /// The readings the collector takes for the active view on top of the system metrics
+    pub(crate) fn snapshot_request(&self) -> SnapshotRequest {
+        SnapshotRequest {
+            process_details: match self.active_tab {
+                Tab::Processes => self.process_view.details_pid(),
+                _ => None,
+            },
+            cgroup_pressure: self.active_tab == Tab::Pressure,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::App;
+    use crate::config::Config;
+    use crate::keymap::Action;
+    use crate::ui::testing::{find_line, render, snapshot};
+    use crate::ui::TableSortPredicate;
+
+    #[test]
+    fn a_reload_keeps_the_sort_picked_with_the_keys() {
+        let mut config = Config::default();
+        let mut app = App::new(&config);
+        app.update(snapshot());
+        app.handle_action(Action::Processes);
+        app.handle_action(Action::SortByCpu);
+
+        app.configure(&config);
+        let lines = render(120, 30, |frame| app.render(frame));
+        find_line(&lines, "CPU ▲");
+
+        config.processes.sort = TableSortPredicate::Memory;
+        app.configure(&config);
+        let lines = render(120, 30, |frame| app.render(frame));
+        find_line(&lines, "Memory ▲");
+    }
 }
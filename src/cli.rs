@@ -0,0 +1,115 @@
+use crate::config::MIN_TICK_RATE;
+use crate::ui::{ColorMode, Tab, TableSort, TableSortPredicate};
+use clap::Parser;
+use std::path::PathBuf;
+
+/// A terminal system monitor.
+/// The options override the settings of the configuration file.
+#[derive(Parser)]
+#[command(version, about)]
+pub(crate) struct Args {
+    /// Interval between two updates, in milliseconds [default: 1000]
+    #[arg(
+        short,
+        long,
+        value_name = "MILLISECONDS",
+        value_parser = clap::value_parser!(u64).range(MIN_TICK_RATE..),
+    )]
+    pub tick_rate: Option<u64>,
+
+    /// Tab displayed at start [default: overview]
+    #[arg(long, value_enum)]
+    pub tab: Option<Tab>,
+
+    /// Column the processes are sorted by [default: name]
+    #[arg(short, long, value_enum, value_name = "COLUMN")]
+    pub sort: Option<TableSortPredicate>,
+
+    /// Order of the sorted processes [default: ascending]
+    #[arg(short, long, value_enum)]
+    pub order: Option<TableSort>,
+
+    /// List only these processes, separated by commas
+    #[arg(short, long = "pid", value_name = "PID", value_delimiter = ',')]
+    pub pids: Vec<u32>,
+
+    /// List only the processes of this user
+    #[arg(short, long)]
+    pub user: Option<String>,
+
+    /// When to draw with colours [default: auto]
+    #[arg(long, value_enum, value_name = "WHEN")]
+    pub color: Option<ColorMode>,
+
+    /// Colour theme: dark, light, high-contrast or a theme file [default: dark]
+    #[arg(long, value_name = "NAME")]
+    pub theme: Option<String>,
+
+    /// Read the sensors from this directory instead of /sys/class/hwmon
+    #[arg(long, value_name = "PATH")]
+    pub hwmon_root: Option<PathBuf>,
+
+    /// Read the configuration from this file
+    /// [default: $XDG_CONFIG_HOME/heimdal/config.toml]
+    #[arg(short, long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Args;
+    use crate::ui::{Tab, TableSort, TableSortPredicate};
+    use clap::{CommandFactory, Parser};
+
+    #[test]
+    fn arguments_are_consistent() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn leaves_the_configured_settings_alone_by_default() {
+        let args = Args::try_parse_from(["heimdal"]).unwrap();
+
+        assert!(args.tick_rate.is_none());
+        assert!(args.tab.is_none());
+        assert!(args.sort.is_none());
+        assert!(args.order.is_none());
+        assert!(args.color.is_none());
+        assert!(args.theme.is_none());
+        assert!(args.hwmon_root.is_none());
+        assert!(args.config.is_none());
+        assert!(args.pids.is_empty());
+        assert!(args.user.is_none());
+    }
+
+    #[test]
+    fn parses_the_process_options() {
+        let args = Args::try_parse_from([
+            "heimdal",
+            "--tab",
+            "processes",
+            "-s",
+            "cpu",
+            "-o",
+            "desc",
+            "-p",
+            "1,42",
+            "-p",
+            "7",
+            "-u",
+            "alice",
+        ])
+        .unwrap();
+
+        assert!(matches!(args.tab, Some(Tab::Processes)));
+        assert!(matches!(args.sort, Some(TableSortPredicate::CPU)));
+        assert!(matches!(args.order, Some(TableSort::Descending)));
+        assert_eq!(args.pids, [1, 42, 7]);
+        assert_eq!(args.user.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn rejects_a_too_short_tick_rate() {
+        assert!(Args::try_parse_from(["heimdal", "--tick-rate", "10"]).is_err());
+    }
+}
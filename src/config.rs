@@ -0,0 +1,610 @@
+use crate::cli::Args;
+use crate::core::{HistoryConfig, HWMON_ROOT};
+use crate::keymap::{KeyConfig, Keymap};
+use crate::ui::{
+    ColorDepth, ColorMode, DisplaySettings, Tab, TableSort, TableSortPredicate, Theme, Thresholds,
+    Units, DEFAULT_HISTORY_WINDOWS,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    collections::HashSet,
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// Interval between two updates of the system information, in milliseconds.
+/// Setting it too low means that the collector thread will run a lot and eat up resources.
+pub const DEFAULT_TICK_RATE: u64 = 1000;
+
+/// The collector cannot refresh the processes faster than this
+pub const MIN_TICK_RATE: u64 = 100;
+
+/// Settings of `config.toml`. Missing settings keep their default,
+/// the command line options override them.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    /// Interval between two updates, in milliseconds
+    pub tick_rate: u64,
+    /// Tab displayed at start, the first visible tab if it is not set
+    pub default_tab: Option<Tab>,
+    /// Visible tabs, in order
+    pub tabs: Vec<Tab>,
+    pub color: ColorMode,
+    /// Colours the terminal supports, guessed from `COLORTERM` and `TERM` by default
+    pub color_depth: ColorDepth,
+    /// A built-in theme, or a file in the `themes` directory next to this file
+    pub theme: String,
+    pub units: Units,
+    pub thresholds: Thresholds,
+    pub processes: ProcessConfig,
+    pub history: HistorySettings,
+    /// Directory of the hardware monitoring chips, it is read from the next start on
+    pub hwmon_root: PathBuf,
+    pub keys: KeyConfig,
+    /// The colours of `theme`, read when the configuration is loaded
+    #[serde(skip)]
+    theme_colors: Theme,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            tick_rate: DEFAULT_TICK_RATE,
+            default_tab: None,
+            tabs: Tab::ALL.to_vec(),
+            color: ColorMode::default(),
+            color_depth: ColorDepth::default(),
+            theme: String::from("dark"),
+            units: Units::default(),
+            thresholds: Thresholds::default(),
+            processes: ProcessConfig::default(),
+            history: HistorySettings::default(),
+            hwmon_root: PathBuf::from(HWMON_ROOT),
+            keys: KeyConfig::default(),
+            theme_colors: Theme::default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ProcessConfig {
+    /// Visible columns, in order
+    pub columns: Vec<TableSortPredicate>,
+    pub sort: TableSortPredicate,
+    pub order: TableSort,
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        ProcessConfig {
+            columns: TableSortPredicate::ALL.to_vec(),
+            sort: TableSortPredicate::default(),
+            order: TableSort::default(),
+        }
+    }
+}
+
+/// How long the metrics are kept, in seconds
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct HistorySettings {
+    pub retention: u64,
+    /// Samples older than this are averaged into buckets of `downsample_interval`
+    pub full_resolution: u64,
+    pub downsample_interval: u64,
+    /// Lengths of the CPU usage charts the window key switches between
+    pub cpu_windows: Vec<u64>,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        let config = HistoryConfig::default();
+        HistorySettings {
+            retention: config.retention.as_secs(),
+            full_resolution: config.full_resolution.as_secs(),
+            downsample_interval: config.downsample_interval.as_secs(),
+            cpu_windows: DEFAULT_HISTORY_WINDOWS
+                .iter()
+                .map(Duration::as_secs)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum ConfigError {
+    Read(PathBuf, io::Error),
+    /// The line is missing when the error is not at a specific place of the file
+    Parse {
+        path: PathBuf,
+        line: Option<usize>,
+        message: String,
+    },
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, error) => {
+                write!(f, "cannot read {}: {}", path.display(), error)
+            }
+            ConfigError::Parse {
+                path,
+                line: Some(line),
+                message,
+            } => write!(
+                f,
+                "cannot parse {} at line {}: {}",
+                path.display(),
+                line,
+                message
+            ),
+            ConfigError::Parse {
+                path,
+                line: None,
+                message,
+            } => write!(f, "cannot parse {}: {}", path.display(), message),
+            ConfigError::Invalid(message) => write!(f, "invalid configuration: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Returns `$XDG_CONFIG_HOME/heimdal/config.toml`, or `~/.config/heimdal/config.toml`
+/// if `XDG_CONFIG_HOME` is not set
+pub(crate) fn default_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        // relative paths are invalid per the XDG specification and must be ignored
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("heimdal").join("config.toml"))
+}
+
+impl Config {
+    /// Reads the configuration from `path` and applies the command line options on top.
+    /// A missing file gives the default configuration, unless it was given on the command line.
+    pub(crate) fn load(path: Option<&Path>, args: &Args) -> Result<Config, ConfigError> {
+        let mut config = match path {
+            Some(path) if args.config.is_some() || path.exists() => {
+                let text = fs::read_to_string(path)
+                    .map_err(|error| ConfigError::Read(path.to_path_buf(), error))?;
+                Config::parse(path, &text)?
+            }
+            _ => Config::default(),
+        };
+        config.apply_args(args);
+        config.validate()?;
+        config.theme_colors = config.load_theme(path.and_then(Path::parent))?;
+        Ok(config)
+    }
+
+    fn parse(path: &Path, text: &str) -> Result<Config, ConfigError> {
+        parse_toml(path, text)
+    }
+
+    /// Returns a built-in theme or reads the theme file. The file is looked up in the `themes`
+    /// directory of the configuration, unless the theme is given as a path to a `.toml` file.
+    fn load_theme(&self, config_dir: Option<&Path>) -> Result<Theme, ConfigError> {
+        if let Some(theme) = Theme::built_in(&self.theme) {
+            return Ok(theme);
+        }
+
+        let config_dir = config_dir.unwrap_or(Path::new("."));
+        let path = if self.theme.ends_with(".toml") {
+            config_dir.join(&self.theme)
+        } else {
+            config_dir
+                .join("themes")
+                .join(format!("{}.toml", self.theme))
+        };
+        if !path.exists() {
+            return Err(ConfigError::Invalid(format!(
+                "the theme {} is not one of {} and {} does not exist",
+                self.theme,
+                Theme::BUILT_IN.join(", "),
+                path.display()
+            )));
+        }
+        let text =
+            fs::read_to_string(&path).map_err(|error| ConfigError::Read(path.clone(), error))?;
+        parse_toml(&path, &text)
+    }
+
+    pub(crate) fn start_tab(&self) -> Tab {
+        self.default_tab.unwrap_or(self.tabs[0])
+    }
+
+    pub(crate) fn display_settings(&self) -> DisplaySettings {
+        DisplaySettings {
+            units: self.units,
+            thresholds: self.thresholds,
+            theme: self.theme_colors,
+        }
+    }
+
+    pub(crate) fn history_config(&self) -> HistoryConfig {
+        HistoryConfig {
+            retention: Duration::from_secs(self.history.retention),
+            full_resolution: Duration::from_secs(self.history.full_resolution),
+            downsample_interval: Duration::from_secs(self.history.downsample_interval),
+        }
+    }
+
+    pub(crate) fn cpu_windows(&self) -> Vec<Duration> {
+        self.history
+            .cpu_windows
+            .iter()
+            .copied()
+            .map(Duration::from_secs)
+            .collect()
+    }
+
+    /// The bindings of `[keys]`, they are checked when the configuration is loaded
+    pub(crate) fn keymap(&self) -> Keymap {
+        Keymap::new(&self.keys).expect("the key bindings were validated")
+    }
+
+    fn apply_args(&mut self, args: &Args) {
+        if let Some(tick_rate) = args.tick_rate {
+            self.tick_rate = tick_rate;
+        }
+        if let Some(tab) = args.tab {
+            self.default_tab = Some(tab);
+        }
+        if let Some(sort) = args.sort {
+            self.processes.sort = sort;
+        }
+        if let Some(order) = args.order {
+            self.processes.order = order;
+        }
+        if let Some(color) = args.color {
+            self.color = color;
+        }
+        if let Some(theme) = &args.theme {
+            self.theme = theme.clone();
+        }
+        if let Some(hwmon_root) = &args.hwmon_root {
+            self.hwmon_root = hwmon_root.clone();
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |message: String| Err(ConfigError::Invalid(message));
+
+        if self.tick_rate < MIN_TICK_RATE {
+            return invalid(format!(
+                "tick_rate is {} ms, it must be at least {} ms",
+                self.tick_rate, MIN_TICK_RATE
+            ));
+        }
+
+        if self.tabs.is_empty() {
+            return invalid(String::from("tabs must list at least one tab"));
+        }
+        if let Some(tab) = first_duplicate(&self.tabs) {
+            return invalid(format!("tabs lists {} more than once", tab.title()));
+        }
+        if let Some(tab) = self.default_tab.filter(|tab| !self.tabs.contains(tab)) {
+            return invalid(format!(
+                "the default tab {} is not one of the visible tabs",
+                tab.title()
+            ));
+        }
+
+        let columns = &self.processes.columns;
+        if columns.is_empty() {
+            return invalid(String::from(
+                "processes.columns must list at least one column",
+            ));
+        }
+        if first_duplicate(columns).is_some() {
+            return invalid(String::from(
+                "processes.columns lists a column more than once",
+            ));
+        }
+        if !columns.contains(&self.processes.sort) {
+            return invalid(String::from(
+                "processes.sort must be one of the visible processes.columns",
+            ));
+        }
+
+        let Thresholds {
+            warning,
+            high,
+            critical,
+        } = self.thresholds;
+        if !(warning <= high && high <= critical && critical <= 100) {
+            return invalid(format!(
+                "thresholds must satisfy warning <= high <= critical <= 100, they are {}, {} and {}",
+                warning, high, critical
+            ));
+        }
+
+        let HistorySettings {
+            retention,
+            full_resolution,
+            downsample_interval,
+            ref cpu_windows,
+        } = self.history;
+        if !(0 < downsample_interval
+            && downsample_interval <= full_resolution
+            && full_resolution <= retention)
+        {
+            return invalid(format!(
+                "history must satisfy 0 < downsample_interval <= full_resolution <= retention, \
+                 they are {}, {} and {} s",
+                downsample_interval, full_resolution, retention
+            ));
+        }
+        if cpu_windows.is_empty() {
+            return invalid(String::from(
+                "history.cpu_windows must list at least one window",
+            ));
+        }
+        if let Some(window) = cpu_windows
+            .iter()
+            .find(|&&window| window == 0 || window > retention)
+        {
+            return invalid(format!(
+                "history.cpu_windows lists {} s, the windows must be between 1 s and the retention of {} s",
+                window, retention
+            ));
+        }
+
+        Keymap::new(&self.keys).map_err(ConfigError::Invalid)?;
+
+        Ok(())
+    }
+}
+
+/// Parses a configuration or theme file, the errors tell the line of the mistake
+fn parse_toml<T: DeserializeOwned>(path: &Path, text: &str) -> Result<T, ConfigError> {
+    toml::from_str(text).map_err(|error| ConfigError::Parse {
+        path: path.to_path_buf(),
+        line: error
+            .span()
+            .map(|span| text[..span.start].matches('\n').count() + 1),
+        // the message is a single line, unlike the error with the snippet of the file
+        message: error.message().to_string(),
+    })
+}
+
+fn first_duplicate<T: Copy + Eq + std::hash::Hash>(items: &[T]) -> Option<T> {
+    let mut seen = HashSet::new();
+    items.iter().copied().find(|item| !seen.insert(*item))
+}
+
+/// Notices when the configuration file is created, changed or removed.
+/// It is polled on every update, so it needs no thread of its own.
+pub(crate) struct ConfigWatcher {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    pub(crate) fn new(path: Option<PathBuf>) -> Self {
+        let modified = path.as_deref().and_then(modification_time);
+        ConfigWatcher { path, modified }
+    }
+
+    pub(crate) fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns true if the file changed since the last call
+    pub(crate) fn changed(&mut self) -> bool {
+        let modified = self.path.as_deref().and_then(modification_time);
+        let changed = modified != self.modified;
+        self.modified = modified;
+        changed
+    }
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, ConfigError, DEFAULT_TICK_RATE};
+    use crate::cli::Args;
+    use crate::core::Key;
+    use crate::keymap::Action;
+    use crate::ui::{Tab, TableSort, TableSortPredicate, Theme, Units};
+    use clap::Parser;
+    use std::{path::Path, time::Duration};
+
+    fn parse(text: &str) -> Result<Config, ConfigError> {
+        parse_with_args(text, &["heimdal"])
+    }
+
+    fn parse_with_args(text: &str, args: &[&str]) -> Result<Config, ConfigError> {
+        let mut config = Config::parse(Path::new("config.toml"), text)?;
+        config.apply_args(&Args::try_parse_from(args).unwrap());
+        config.validate()?;
+        config.theme_colors = config.load_theme(None)?;
+        Ok(config)
+    }
+
+    #[test]
+    fn an_empty_file_gives_the_defaults() {
+        let config = parse("").unwrap();
+
+        assert_eq!(config.tick_rate, DEFAULT_TICK_RATE);
+        assert_eq!(config.start_tab(), Tab::Overview);
+        assert_eq!(config.tabs, Tab::ALL);
+        assert!(config.units == Units::Binary);
+        assert_eq!(config.processes.columns, TableSortPredicate::ALL);
+        assert_eq!(config.keymap().action(Key::Char('q')), Some(Action::Quit));
+    }
+
+    #[test]
+    fn parses_every_setting() {
+        let config = parse(
+            r#"
+            tick_rate = 2000
+            default_tab = "processes"
+            tabs = ["processes", "cpu", "memory"]
+            color = "never"
+            units = "decimal"
+            hwmon_root = "/tmp/hwmon"
+
+            [thresholds]
+            warning = 60
+            high = 80
+            critical = 95
+
+            [processes]
+            columns = ["pid", "name", "cpu"]
+            sort = "cpu"
+            order = "desc"
+
+            [history]
+            retention = 7200
+            full_resolution = 300
+            downsample_interval = 30
+            cpu_windows = [30, 3600]
+
+            [keys]
+            preset = "vim"
+            quit = "x"
+            processes = ["l", "F2"]
+            right = "Ctrl-l"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.tick_rate, 2000);
+        assert_eq!(config.start_tab(), Tab::Processes);
+        assert_eq!(config.tabs, [Tab::Processes, Tab::CPU, Tab::Memory]);
+        assert!(config.units == Units::Decimal);
+        assert_eq!(config.hwmon_root, Path::new("/tmp/hwmon"));
+        assert_eq!(config.thresholds.high, 80);
+        assert_eq!(
+            config.processes.columns,
+            [
+                TableSortPredicate::PID,
+                TableSortPredicate::Name,
+                TableSortPredicate::CPU
+            ]
+        );
+        assert_eq!(config.processes.sort, TableSortPredicate::CPU);
+        assert!(matches!(config.processes.order, TableSort::Descending));
+        let history = config.history_config();
+        assert_eq!(history.retention, Duration::from_secs(7200));
+        assert_eq!(history.full_resolution, Duration::from_secs(300));
+        assert_eq!(history.downsample_interval, Duration::from_secs(30));
+        assert_eq!(
+            config.cpu_windows(),
+            [Duration::from_secs(30), Duration::from_secs(3600)]
+        );
+        let keymap = config.keymap();
+        assert_eq!(keymap.action(Key::Char('x')), Some(Action::Quit));
+        assert_eq!(keymap.action(Key::Char('q')), None);
+        assert_eq!(keymap.action(Key::Char('l')), Some(Action::Processes));
+        assert_eq!(keymap.action(Key::F(2)), Some(Action::Processes));
+        assert_eq!(keymap.action(Key::Ctrl('l')), Some(Action::Right));
+        assert_eq!(keymap.action(Key::Char('c')), Some(Action::Cpu));
+        assert_eq!(keymap.action(Key::Char('j')), Some(Action::Down));
+    }
+
+    #[test]
+    fn loads_the_theme() {
+        assert_eq!(parse("").unwrap().display_settings().theme, Theme::DARK);
+        let config = parse_with_args(
+            "theme = \"light\"",
+            &["heimdal", "--theme", "high-contrast"],
+        );
+        assert_eq!(
+            config.unwrap().display_settings().theme,
+            Theme::HIGH_CONTRAST
+        );
+
+        match parse("theme = \"solarized\"") {
+            Err(ConfigError::Invalid(message)) => assert!(
+                message.contains("not one of dark, light, high-contrast")
+                    && message.contains("solarized.toml"),
+                "{}",
+                message
+            ),
+            Err(error) => panic!("{} is not a validation error", error),
+            Ok(_) => panic!("the missing theme was accepted"),
+        }
+    }
+
+    #[test]
+    fn starts_on_the_first_visible_tab_by_default() {
+        let config = parse(r#"tabs = ["network", "cpu"]"#).unwrap();
+
+        assert_eq!(config.start_tab(), Tab::Network);
+    }
+
+    #[test]
+    fn the_command_line_overrides_the_file() {
+        let config = parse_with_args(
+            "tick_rate = 2000\ndefault_tab = \"cpu\"\nhwmon_root = \"/tmp/hwmon\"",
+            &[
+                "heimdal",
+                "-t",
+                "500",
+                "--tab",
+                "memory",
+                "--hwmon-root",
+                "/tmp/fixture",
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.tick_rate, 500);
+        assert_eq!(config.hwmon_root, Path::new("/tmp/fixture"));
+        assert_eq!(config.start_tab(), Tab::Memory);
+    }
+
+    #[test]
+    fn reports_unknown_settings_with_their_position() {
+        let error = match parse("tick_rate = 1000\nrefresh = 2") {
+            Err(error) => error.to_string(),
+            Ok(_) => panic!("the unknown setting was accepted"),
+        };
+
+        assert!(
+            error.starts_with("cannot parse config.toml at line 2: unknown field `refresh`"),
+            "{}",
+            error
+        );
+        assert!(!error.contains('\n'), "{}", error);
+    }
+
+    #[test]
+    fn rejects_inconsistent_settings() {
+        let invalid = |text: &str| match parse(text) {
+            Err(ConfigError::Invalid(message)) => message,
+            Err(error) => panic!("{} is not a validation error", error),
+            Ok(_) => panic!("{:?} was accepted", text),
+        };
+
+        assert!(invalid("tick_rate = 10").contains("at least 100 ms"));
+        assert!(invalid("tabs = []").contains("at least one tab"));
+        assert!(invalid(r#"tabs = ["cpu", "cpu"]"#).contains("CPU more than once"));
+        assert!(
+            invalid("default_tab = \"memory\"\ntabs = [\"cpu\"]").contains("default tab Memory")
+        );
+        assert!(invalid("[processes]\ncolumns = [\"pid\"]").contains("processes.sort"));
+        assert!(invalid("[thresholds]\nwarning = 80\nhigh = 70").contains("warning <= high"));
+        assert!(invalid("[history]\nretention = 60").contains("full_resolution <= retention"));
+        assert!(invalid("[history]\ndownsample_interval = 0").contains("0 < downsample_interval"));
+        assert!(invalid("[history]\ncpu_windows = []").contains("at least one window"));
+        assert!(invalid("[history]\ncpu_windows = [60, 7200]").contains("lists 7200 s"));
+        assert!(invalid("[history]\ncpu_windows = [0]").contains("lists 0 s"));
+        assert!(invalid("[keys]\nquit = \"c\"").contains("'c' is bound more than once"));
+        assert!(invalid("[keys]\nquit = \"Hyper-q\"").contains("unknown key \"Hyper-q\""));
+    }
+}
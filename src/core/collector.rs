@@ -0,0 +1,67 @@
+use super::{Event, MetricsSource, SnapshotRequest};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::Sender,
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Takes snapshots from a source on its own thread.
+pub struct Collector<S> {
+    source: S,
+}
+
+impl<S: MetricsSource + Send + 'static> Collector<S> {
+    pub fn new(source: S) -> Self {
+        Collector { source }
+    }
+
+    /// Starts sampling every `interval` and sends each snapshot as an `Event::Update`.
+    /// The thread stops once the receiving side of `tx` is dropped.
+    pub fn spawn<I: Send + 'static>(
+        mut self,
+        tx: Sender<Event<I>>,
+        interval: Duration,
+    ) -> CollectorHandle {
+        let handle = CollectorHandle {
+            interval_ms: Arc::new(AtomicU64::new(interval.as_millis() as u64)),
+            request: Arc::new(Mutex::new(SnapshotRequest::default())),
+        };
+        let interval_ms = Arc::clone(&handle.interval_ms);
+        let request = Arc::clone(&handle.request);
+
+        thread::spawn(move || loop {
+            let started = Instant::now();
+            let request = *request.lock().unwrap();
+            let snapshot = self.source.collect(&request);
+            if tx.send(Event::Update(Box::new(snapshot))).is_err() {
+                break;
+            }
+            let interval = Duration::from_millis(interval_ms.load(Ordering::Relaxed));
+            thread::sleep(interval.saturating_sub(started.elapsed()));
+        });
+        handle
+    }
+}
+
+/// Controls a running collector thread
+pub struct CollectorHandle {
+    interval_ms: Arc<AtomicU64>,
+    request: Arc<Mutex<SnapshotRequest>>,
+}
+
+impl CollectorHandle {
+    /// Changes the sampling interval, it applies from the next snapshot on
+    pub fn set_interval(&self, interval: Duration) {
+        self.interval_ms
+            .store(interval.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Changes the optional readings, they are taken from the next snapshot on
+    pub fn set_request(&self, request: SnapshotRequest) {
+        *self.request.lock().unwrap() = request;
+    }
+}
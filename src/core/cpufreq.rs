@@ -0,0 +1,154 @@
+use super::sysfs::{read_number, read_string};
+use std::{fs, io, path::Path};
+
+/// Where the kernel exposes the logical CPUs
+pub const CPU_SYSFS_ROOT: &str = "/sys/devices/system/cpu";
+
+/// Frequency scaling state and thermal throttle counters of a logical CPU.
+/// Frequencies are in kHz. Every value is optional: cpufreq is missing in most
+/// virtual machines and the throttle counters are only provided on Intel CPUs.
+#[derive(Clone)]
+pub struct CpuFrequency {
+    pub cpu: usize,
+    pub current: Option<u64>,
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    pub governor: Option<String>,
+    pub energy_performance_preference: Option<String>,
+    pub core_throttle_count: Option<u64>,
+    pub package_throttle_count: Option<u64>,
+}
+
+impl CpuFrequency {
+    /// Reads the state of every logical CPU below `root`, which is normally CPU_SYSFS_ROOT,
+    /// ordered by CPU number
+    pub fn read_all(root: &Path) -> io::Result<Vec<Self>> {
+        let mut frequencies = Vec::new();
+
+        for entry in fs::read_dir(root)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            // skip cpufreq, cpuidle and the other non-CPU directories
+            let cpu = match file_name
+                .to_str()
+                .and_then(|name| name.strip_prefix("cpu"))
+                .and_then(|number| number.parse::<usize>().ok())
+            {
+                Some(cpu) => cpu,
+                None => continue,
+            };
+            frequencies.push(Self::read(cpu, &entry.path()));
+        }
+
+        frequencies.sort_by_key(|frequency| frequency.cpu);
+        Ok(frequencies)
+    }
+
+    fn read(cpu: usize, path: &Path) -> Self {
+        let cpufreq = path.join("cpufreq");
+        let thermal_throttle = path.join("thermal_throttle");
+
+        CpuFrequency {
+            cpu,
+            current: read_number(&cpufreq.join("scaling_cur_freq"))
+                .or_else(|| read_number(&cpufreq.join("cpuinfo_cur_freq"))),
+            min: read_number(&cpufreq.join("scaling_min_freq"))
+                .or_else(|| read_number(&cpufreq.join("cpuinfo_min_freq"))),
+            max: read_number(&cpufreq.join("scaling_max_freq"))
+                .or_else(|| read_number(&cpufreq.join("cpuinfo_max_freq"))),
+            governor: read_string(&cpufreq.join("scaling_governor")),
+            energy_performance_preference: read_string(
+                &cpufreq.join("energy_performance_preference"),
+            ),
+            core_throttle_count: read_number(&thermal_throttle.join("core_throttle_count")),
+            package_throttle_count: read_number(&thermal_throttle.join("package_throttle_count")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CpuFrequency;
+    use std::{fs, path::Path};
+
+    fn write_attributes(directory: &Path, attributes: &[(&str, &str)]) {
+        fs::create_dir_all(directory).unwrap();
+        for (attribute, value) in attributes {
+            fs::write(directory.join(attribute), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn reads_the_frequencies_and_throttle_counters_of_every_cpu() {
+        let root = tempfile::tempdir().unwrap();
+        let cpu0 = root.path().join("cpu0");
+        write_attributes(
+            &cpu0.join("cpufreq"),
+            &[
+                ("scaling_cur_freq", "2400000"),
+                ("scaling_min_freq", "800000"),
+                ("scaling_max_freq", "4200000"),
+                ("cpuinfo_max_freq", "4700000"),
+                ("scaling_governor", "powersave"),
+                ("energy_performance_preference", "balance_performance"),
+            ],
+        );
+        write_attributes(
+            &cpu0.join("thermal_throttle"),
+            &[
+                ("core_throttle_count", "12"),
+                ("package_throttle_count", "3"),
+            ],
+        );
+        // only the hardware limits are readable without the scaling driver
+        write_attributes(
+            &root.path().join("cpu10").join("cpufreq"),
+            &[
+                ("cpuinfo_cur_freq", "1900000"),
+                ("cpuinfo_min_freq", "400000"),
+                ("cpuinfo_max_freq", "3600000"),
+            ],
+        );
+        // a virtual machine without cpufreq
+        fs::create_dir(root.path().join("cpu2")).unwrap();
+        // directories which are not CPUs
+        fs::create_dir(root.path().join("cpufreq")).unwrap();
+        fs::create_dir(root.path().join("cpuidle")).unwrap();
+
+        let frequencies = CpuFrequency::read_all(root.path()).unwrap();
+
+        let cpus: Vec<usize> = frequencies.iter().map(|frequency| frequency.cpu).collect();
+        assert_eq!(cpus, [0, 2, 10]);
+        let cpu0 = &frequencies[0];
+        assert_eq!(
+            (cpu0.current, cpu0.min, cpu0.max),
+            (Some(2_400_000), Some(800_000), Some(4_200_000))
+        );
+        assert_eq!(cpu0.governor.as_deref(), Some("powersave"));
+        assert_eq!(
+            cpu0.energy_performance_preference.as_deref(),
+            Some("balance_performance")
+        );
+        assert_eq!(cpu0.core_throttle_count, Some(12));
+        assert_eq!(cpu0.package_throttle_count, Some(3));
+
+        let cpu2 = &frequencies[1];
+        assert_eq!((cpu2.current, cpu2.min, cpu2.max), (None, None, None));
+        assert!(cpu2.governor.is_none() && cpu2.energy_performance_preference.is_none());
+        assert!(cpu2.core_throttle_count.is_none() && cpu2.package_throttle_count.is_none());
+
+        let cpu10 = &frequencies[2];
+        assert_eq!(
+            (cpu10.current, cpu10.min, cpu10.max),
+            (Some(1_900_000), Some(400_000), Some(3_600_000))
+        );
+        assert!(cpu10.governor.is_none());
+    }
+
+    #[test]
+    fn fails_without_the_cpu_directory() {
+        let root = tempfile::tempdir().unwrap();
+
+        assert!(CpuFrequency::read_all(&root.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fs, io};
+
+/// Cumulative time a CPU spent in each state since boot, in USER_HZ ticks,
+/// parsed from a `cpu` line of `/proc/stat`.
+#[derive(Clone, Copy, Default)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+    pub guest: u64,
+    pub guest_nice: u64,
+}
+
+/// Share of each state between two samples in percent.
+/// `user` and `nice` do not include the guest time, so the shares add up to 100.
+#[derive(Clone, Copy, Default)]
+pub struct CpuBreakdown {
+    pub user: f64,
+    pub nice: f64,
+    pub system: f64,
+    pub idle: f64,
+    pub iowait: f64,
+    pub irq: f64,
+    pub softirq: f64,
+    pub steal: f64,
+    pub guest: f64,
+}
+
+impl CpuTimes {
+    /// Computes the share of each state in the time elapsed since `previous`
+    pub fn breakdown_since(&self, previous: &CpuTimes) -> CpuBreakdown {
+        let delta = |current: u64, previous: u64| current.saturating_sub(previous) as f64;
+        // the kernel accounts the guest time in user and nice too
+        let guest = delta(self.guest, previous.guest);
+        let guest_nice = delta(self.guest_nice, previous.guest_nice);
+        let user = (delta(self.user, previous.user) - guest).max(0.0);
+        let nice = (delta(self.nice, previous.nice) - guest_nice).max(0.0);
+        let system = delta(self.system, previous.system);
+        let idle = delta(self.idle, previous.idle);
+        let iowait = delta(self.iowait, previous.iowait);
+        let irq = delta(self.irq, previous.irq);
+        let softirq = delta(self.softirq, previous.softirq);
+        let steal = delta(self.steal, previous.steal);
+        let guest = guest + guest_nice;
+
+        let total = user + nice + system + idle + iowait + irq + softirq + steal + guest;
+        if total == 0.0 {
+            return CpuBreakdown::default();
+        }
+        let percent = |time: f64| time / total * 100.0;
+
+        CpuBreakdown {
+            user: percent(user),
+            nice: percent(nice),
+            system: percent(system),
+            idle: percent(idle),
+            iowait: percent(iowait),
+            irq: percent(irq),
+            softirq: percent(softirq),
+            steal: percent(steal),
+            guest: percent(guest),
+        }
+    }
+}
+
+/// CPU times of the whole machine and of every core.
+#[derive(Clone, Default)]
+pub struct CpuStat {
+    pub total: CpuTimes,
+    pub cores: Vec<CpuTimes>,
+}
+
+impl CpuStat {
+    /// Reads `/proc/stat`
+    pub fn read() -> io::Result<Self> {
+        let content = fs::read_to_string("/proc/stat")?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Parses the `cpu` lines of a stat file. Missing columns of older kernels are left at 0.
+    pub fn parse(content: &str) -> Self {
+        let mut stat = CpuStat::default();
+
+        for line in content.lines() {
+            let mut fields = line.split_whitespace();
+            let name = match fields.next() {
+                Some(name) if name.starts_with("cpu") => name,
+                _ => continue,
+            };
+            let values: Vec<u64> = fields.map(|value| value.parse().unwrap_or(0)).collect();
+            let value = |index: usize| values.get(index).copied().unwrap_or(0);
+            let times = CpuTimes {
+                user: value(0),
+                nice: value(1),
+                system: value(2),
+                idle: value(3),
+                iowait: value(4),
+                irq: value(5),
+                softirq: value(6),
+                steal: value(7),
+                guest: value(8),
+                guest_nice: value(9),
+            };
+
+            if name == "cpu" {
+                stat.total = times;
+            } else {
+                stat.cores.push(times);
+            }
+        }
+
+        stat
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CpuStat;
+
+    const STAT: &str = "\
+cpu  269612 1200 23405 511808 404 10 17 1043 300 20
+cpu0 134806 600 11702 255904 202 5 8 521 150 10
+cpu1 134806 600 11703 255904 202 5 9 522 150 10
+intr 1032360 0 0 0 0 0 0 0 0 0 0 0 0 0
+ctxt 3155112
+btime 1700000000
+processes 10234
+procs_running 2
+procs_blocked 0
+softirq 559324 0 182746 2 16455 0 0 1 232270 0 127850
+";
+
+    #[test]
+    fn parses_a_stat_file() {
+        let stat = CpuStat::parse(STAT);
+
+        assert_eq!(stat.cores.len(), 2);
+        let total = stat.total;
+        assert_eq!(
+            (
+                total.user,
+                total.nice,
+                total.system,
+                total.idle,
+                total.iowait
+            ),
+            (269_612, 1_200, 23_405, 511_808, 404)
+        );
+        assert_eq!(
+            (
+                total.irq,
+                total.softirq,
+                total.steal,
+                total.guest,
+                total.guest_nice
+            ),
+            (10, 17, 1_043, 300, 20)
+        );
+        assert_eq!(stat.cores[1].system, 11_703);
+        assert_eq!(stat.cores[1].steal, 522);
+    }
+
+    #[test]
+    fn leaves_missing_columns_at_zero() {
+        // kernels before 2.6.33 have no guest_nice, before 2.6.11 no steal and guest
+        let stat = CpuStat::parse("cpu  100 2 30 400 5 6 7\ncpu0 100 2 30 400 5 6 7\n");
+
+        assert_eq!(stat.total.softirq, 7);
+        assert_eq!(stat.total.steal, 0);
+        assert_eq!(stat.total.guest, 0);
+        assert_eq!(stat.total.guest_nice, 0);
+        assert_eq!(stat.cores.len(), 1);
+    }
+
+    #[test]
+    fn parses_truncated_and_malformed_lines() {
+        let stat = CpuStat::parse("cpu  100 x 30\ncpu0\ncpu1 50 1");
+
+        assert_eq!(stat.total.user, 100);
+        assert_eq!(stat.total.nice, 0);
+        assert_eq!(stat.total.system, 30);
+        assert_eq!(stat.total.idle, 0);
+        assert_eq!(stat.cores.len(), 2);
+        assert_eq!(stat.cores[0].user, 0);
+        assert_eq!((stat.cores[1].user, stat.cores[1].nice), (50, 1));
+    }
+
+    #[test]
+    fn breaks_down_the_time_without_counting_the_guest_time_twice() {
+        let previous = CpuStat::parse("cpu  100 0 100 700 0 0 0 0 0 0\n").total;
+        let current = CpuStat::parse("cpu  200 0 150 800 0 0 0 0 50 0\n").total;
+        let breakdown = current.breakdown_since(&previous);
+
+        // 250 ticks: 50 user, 50 system, 100 idle and 50 guest
+        assert_eq!(breakdown.user, 20.0);
+        assert_eq!(breakdown.system, 20.0);
+        assert_eq!(breakdown.idle, 40.0);
+        assert_eq!(breakdown.guest, 20.0);
+    }
+}
@@ -0,0 +1,104 @@
+use std::{fs, io};
+
+const DISKSTATS_PATH: &str = "/proc/diskstats";
+
+/// The kernel always reports sectors in /proc/diskstats in 512 byte units,
+/// regardless of the real sector size of the device.
+const SECTOR_SIZE: u64 = 512;
+
+/// I/O counters of a single block device parsed from `/proc/diskstats`.
+#[derive(Clone, Default)]
+pub struct DiskStat {
+    pub name: String,
+    pub sectors_read: u64,
+    pub sectors_written: u64,
+    /// Milliseconds spent doing I/O
+    pub io_time: u64,
+}
+
+impl DiskStat {
+    /// Reads and parses the current contents of `/proc/diskstats`.
+    pub fn read_all() -> io::Result<Vec<Self>> {
+        let content = fs::read_to_string(DISKSTATS_PATH)?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Parses the contents of a diskstats file. Malformed lines are skipped.
+    pub fn parse(content: &str) -> Vec<Self> {
+        content.lines().filter_map(Self::parse_line).collect()
+    }
+
+    fn parse_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 14 {
+            return None;
+        }
+        let field = |index: usize| fields[index].parse::<u64>().ok();
+
+        Some(DiskStat {
+            name: fields[2].to_string(),
+            sectors_read: field(5)?,
+            sectors_written: field(9)?,
+            io_time: field(12)?,
+        })
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.sectors_read * SECTOR_SIZE
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.sectors_written * SECTOR_SIZE
+    }
+
+    /// Loop and ram devices are present on almost every system but rarely interesting.
+    pub fn is_virtual(&self) -> bool {
+        self.name.starts_with("loop") || self.name.starts_with("ram")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiskStat;
+
+    // kernels before 4.18 have 14 fields, 4.18 added discards and 5.5 flushes
+    const DISKSTATS: &str = "\
+   7       0 loop0 52 0 2146 13 0 0 0 0 0 48 13 0 0 0 0 0 0
+ 259       0 nvme0n1 276894 82471 19264270 61245 572198 331127 37195874 468311 0 312140 547932 0 0 0 0 22016 18375
+ 259       1 nvme0n1p1 412 1210 14396 87 2 0 2 1 0 120 88 0 0 0 0 0 0
+   8       0 sda 9520 2103 704562 40310 1221 893 60344 9904 0 30432 50214
+";
+
+    #[test]
+    fn parses_a_diskstats_file() {
+        let stats = DiskStat::parse(DISKSTATS);
+
+        let names: Vec<&str> = stats.iter().map(|stat| stat.name.as_str()).collect();
+        assert_eq!(names, ["loop0", "nvme0n1", "nvme0n1p1", "sda"]);
+        let nvme = &stats[1];
+        assert_eq!(nvme.sectors_read, 19_264_270);
+        assert_eq!(nvme.sectors_written, 37_195_874);
+        assert_eq!(nvme.io_time, 312_140);
+        assert_eq!(nvme.bytes_read(), 19_264_270 * 512);
+        assert_eq!(nvme.bytes_written(), 37_195_874 * 512);
+        assert!(stats[0].is_virtual());
+        assert!(!nvme.is_virtual());
+        assert_eq!(stats[3].io_time, 30_432);
+    }
+
+    #[test]
+    fn skips_truncated_and_malformed_lines() {
+        let stats = DiskStat::parse(
+            "   8       0 sda 9520 2103 704562 40310 1221 893 60344\n\
+             8       16 sdb 9520 2103 70x562 40310 1221 893 60344 9904 0 30432 50214\n\
+             \n\
+             8       32 sdc 1 0 8 0 2 0 16 0 0 3 0\n",
+        );
+
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].name, "sdc");
+        assert_eq!(stats[0].sectors_read, 8);
+        assert_eq!(stats[0].sectors_written, 16);
+        assert_eq!(stats[0].io_time, 3);
+    }
+}
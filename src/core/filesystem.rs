@@ -0,0 +1,44 @@
+use std::{ffi::CString, io, mem::MaybeUninit, os::unix::ffi::OsStrExt, path::Path};
+
+/// Inode counters of a mounted filesystem.
+#[derive(Clone, Copy, Default)]
+pub struct InodeUsage {
+    pub total: u64,
+    pub free: u64,
+}
+
+impl InodeUsage {
+    /// Queries the inode counters of the filesystem mounted at `mount_point` with `statvfs(3)`.
+    pub fn for_mount_point(mount_point: &Path) -> io::Result<Self> {
+        let path = CString::new(mount_point.as_os_str().as_bytes())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+
+        // SAFETY: `path` is a valid NUL terminated string and `stat` is only read after
+        // the call reported success, which guarantees that it has been initialized.
+        let result = unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let stat = unsafe { stat.assume_init() };
+
+        Ok(InodeUsage {
+            total: stat.f_files,
+            free: stat.f_ffree,
+        })
+    }
+
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    /// Percentage of the inodes in use. Filesystems without a fixed inode table
+    /// (e.g. btrfs) report zero inodes, which is treated as no usage.
+    pub fn used_percent(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used() as f64 / self.total as f64 * 100.0
+        }
+    }
+}
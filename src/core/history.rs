@@ -0,0 +1,599 @@
+use super::{DiskStat, Resource, ResourcePressure, Snapshot};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+use sysinfo::Pid;
+
+/// A recorded metric. Usages and pressures are percentages, throughputs are bytes per second,
+/// process memory is in kilobytes and the discharge rate is in W.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Metric {
+    CpuUsage,
+    CoreUsage(usize),
+    MemoryUsed,
+    SwapUsed,
+    NetworkReceived(String),
+    NetworkTransmitted(String),
+    DiskRead(String),
+    DiskWritten(String),
+    /// Share of time the block device was busy
+    DiskBusy(String),
+    /// Only recorded for the process whose details are open
+    ProcessCpu(Pid),
+    ProcessMemory(Pid),
+    /// Power drawn from the discharging batteries
+    BatteryDischarge,
+    /// avg10 of the time at least one task was stalled on the resource
+    PressureSome(Resource),
+    /// avg10 of the time all non-idle tasks were stalled on the resource at once
+    PressureFull(Resource),
+    /// The pressure of a control group, only recorded for the followed one
+    CgroupPressureSome(String, Resource),
+    CgroupPressureFull(String, Resource),
+}
+
+impl Metric {
+    fn process(&self) -> Option<Pid> {
+        match self {
+            Metric::ProcessCpu(pid) | Metric::ProcessMemory(pid) => Some(*pid),
+            _ => None,
+        }
+    }
+
+    fn cgroup(&self) -> Option<&str> {
+        match self {
+            Metric::CgroupPressureSome(name, _) | Metric::CgroupPressureFull(name, _) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// How long samples are kept and how they are thinned out as they age.
+#[derive(Clone, Copy)]
+pub struct HistoryConfig {
+    /// Samples older than this are dropped
+    pub retention: Duration,
+    /// Samples older than this are averaged into buckets of `downsample_interval`
+    pub full_resolution: Duration,
+    pub downsample_interval: Duration,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            retention: Duration::from_secs(60 * 60),
+            full_resolution: Duration::from_secs(10 * 60),
+            downsample_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Sample {
+    pub time: Instant,
+    pub value: f64,
+}
+
+/// Minimum, maximum and average of the samples in a window
+#[derive(Clone, Copy)]
+pub struct SeriesStats {
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+/// Samples averaged together once they are older than the full resolution.
+/// The sum, count and extremes are kept, so window statistics weight the bucket
+/// by its samples and keep their real minimum and maximum.
+#[derive(Clone, Copy)]
+struct Bucket {
+    start: Instant,
+    sum: f64,
+    count: u32,
+    min: f64,
+    max: f64,
+}
+
+impl Bucket {
+    fn new(sample: Sample) -> Self {
+        Bucket {
+            start: sample.time,
+            sum: sample.value,
+            count: 1,
+            min: sample.value,
+            max: sample.value,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn average(&self) -> Sample {
+        Sample {
+            time: self.start,
+            value: self.sum / self.count as f64,
+        }
+    }
+}
+
+/// Samples of a single metric, oldest first.
+/// Recent samples are kept as recorded, older ones as buckets.
+#[derive(Default)]
+pub struct Series {
+    downsampled: VecDeque<Bucket>,
+    /// The bucket the expiring samples are added to
+    bucket: Option<Bucket>,
+    samples: VecDeque<Sample>,
+}
+
+impl Series {
+    fn push(&mut self, sample: Sample, config: &HistoryConfig) {
+        self.samples.push_back(sample);
+
+        let full_resolution = config.full_resolution.min(config.retention);
+        while let Some(oldest) = self.samples.front().copied() {
+            if sample.time.duration_since(oldest.time) <= full_resolution {
+                break;
+            }
+            self.samples.pop_front();
+            self.add_to_bucket(oldest, config);
+        }
+
+        while let Some(oldest) = self.downsampled.front() {
+            if sample.time.duration_since(oldest.start) <= config.retention {
+                break;
+            }
+            self.downsampled.pop_front();
+        }
+    }
+
+    fn add_to_bucket(&mut self, sample: Sample, config: &HistoryConfig) {
+        match &mut self.bucket {
+            Some(bucket)
+                if sample.time.duration_since(bucket.start) < config.downsample_interval =>
+            {
+                bucket.add(sample.value)
+            }
+            _ => {
+                if let Some(bucket) = self.bucket {
+                    self.downsampled.push_back(bucket);
+                }
+                self.bucket = Some(Bucket::new(sample));
+            }
+        }
+    }
+
+    pub fn latest(&self) -> Option<Sample> {
+        self.samples.back().copied()
+    }
+
+    /// Returns the buckets and the samples, as buckets of one, started within `window` before `now`
+    fn buckets(&self, window: Duration, now: Instant) -> impl Iterator<Item = Bucket> + '_ {
+        self.downsampled
+            .iter()
+            .copied()
+            .chain(self.bucket)
+            .chain(self.samples.iter().copied().map(Bucket::new))
+            .filter(move |bucket| now.saturating_duration_since(bucket.start) <= window)
+    }
+
+    /// Returns the samples recorded within `window` before `now`, oldest first.
+    /// Downsampled samples are given as the average of their bucket.
+    pub fn window(&self, window: Duration, now: Instant) -> Vec<Sample> {
+        self.buckets(window, now)
+            .map(|bucket| bucket.average())
+            .collect()
+    }
+
+    pub fn stats(&self, window: Duration, now: Instant) -> Option<SeriesStats> {
+        let total = self.buckets(window, now).reduce(|mut total, bucket| {
+            total.sum += bucket.sum;
+            total.count += bucket.count;
+            total.min = total.min.min(bucket.min);
+            total.max = total.max.max(bucket.max);
+            total
+        })?;
+        Some(SeriesStats {
+            min: total.min,
+            max: total.max,
+            avg: total.average().value,
+        })
+    }
+}
+
+/// Past values of the system metrics, recorded from every snapshot.
+/// Windows are measured back from the last recorded snapshot.
+pub struct MetricsHistory {
+    config: HistoryConfig,
+    series: HashMap<Metric, Series>,
+    /// The control group whose pressure is recorded, there are too many to record them all
+    followed_cgroup: Option<String>,
+    last_record: Option<Instant>,
+    /// Counters of the previous snapshot, the disk throughput is computed from the difference
+    last_disk_stats: Vec<DiskStat>,
+}
+
+impl MetricsHistory {
+    pub fn new(config: HistoryConfig) -> Self {
+        MetricsHistory {
+            config,
+            series: HashMap::new(),
+            followed_cgroup: None,
+            last_record: None,
+            last_disk_stats: Vec::new(),
+        }
+    }
+
+    /// Applies to the samples recorded from now on, the older ones are trimmed as new ones arrive
+    pub fn set_config(&mut self, config: HistoryConfig) {
+        self.config = config;
+    }
+
+    /// Records the pressure of this control group from the next snapshot on,
+    /// the history of the previously followed one is dropped
+    pub fn follow_cgroup(&mut self, name: Option<String>) {
+        self.followed_cgroup = name;
+    }
+
+    /// Records the metrics of a snapshot taken at `now`
+    pub fn record(&mut self, snapshot: &Snapshot, now: Instant) {
+        let elapsed = self
+            .last_record
+            .map(|last_record| now.saturating_duration_since(last_record).as_secs_f64())
+            .unwrap_or(0.0);
+        let per_sec = |amount: u64| {
+            if elapsed > 0.0 {
+                amount as f64 / elapsed
+            } else {
+                0.0
+            }
+        };
+        let mut samples = Vec::new();
+
+        samples.push((Metric::CpuUsage, snapshot.cpu.usage as f64));
+        for (core, usage) in snapshot.cpu.core_usages.iter().enumerate() {
+            samples.push((Metric::CoreUsage(core), *usage as f64));
+        }
+        samples.push((Metric::MemoryUsed, snapshot.memory.meminfo.used_percent()));
+        samples.push((
+            Metric::SwapUsed,
+            snapshot.memory.meminfo.swap_used_percent(),
+        ));
+
+        for network in &snapshot.networks {
+            let name = network.name.clone();
+            samples.push((
+                Metric::NetworkReceived(name.clone()),
+                per_sec(network.received),
+            ));
+            samples.push((
+                Metric::NetworkTransmitted(name),
+                per_sec(network.transmitted),
+            ));
+        }
+
+        for stat in snapshot.disk_stats.iter().filter(|stat| !stat.is_virtual()) {
+            let previous = self
+                .last_disk_stats
+                .iter()
+                .find(|previous| previous.name == stat.name)
+                .unwrap_or(stat);
+            let name = stat.name.clone();
+            samples.push((
+                Metric::DiskRead(name.clone()),
+                per_sec(stat.bytes_read().saturating_sub(previous.bytes_read())),
+            ));
+            samples.push((
+                Metric::DiskWritten(name.clone()),
+                per_sec(
+                    stat.bytes_written()
+                        .saturating_sub(previous.bytes_written()),
+                ),
+            ));
+            // io_time is in milliseconds, so this is already a percentage
+            samples.push((
+                Metric::DiskBusy(name),
+                (per_sec(stat.io_time.saturating_sub(previous.io_time)) / 10.0).min(100.0),
+            ));
+        }
+        self.last_disk_stats = snapshot.disk_stats.clone();
+
+        let batteries = &snapshot.power.supplies.batteries;
+        if !batteries.is_empty() {
+            let discharge_rate = batteries
+                .iter()
+                .filter(|battery| battery.is_discharging())
+                .filter_map(|battery| battery.power)
+                .sum();
+            samples.push((Metric::BatteryDischarge, discharge_rate));
+        }
+
+        push_pressure(
+            &mut samples,
+            &snapshot.pressure,
+            Metric::PressureSome,
+            Metric::PressureFull,
+        );
+        let followed_cgroup = self.followed_cgroup.as_ref().and_then(|name| {
+            snapshot
+                .cgroup_pressure
+                .iter()
+                .flatten()
+                .find(|cgroup| &cgroup.name == name)
+        });
+        if let Some(cgroup) = followed_cgroup {
+            push_pressure(
+                &mut samples,
+                &cgroup.pressure,
+                |resource| Metric::CgroupPressureSome(cgroup.name.clone(), resource),
+                |resource| Metric::CgroupPressureFull(cgroup.name.clone(), resource),
+            );
+        }
+
+        // a series for every process would grow with each one started, so only the process
+        // whose details are open is followed
+        let followed = snapshot
+            .process_details
+            .as_ref()
+            .and_then(|details| snapshot.process(details.pid));
+        if let Some(process) = followed {
+            samples.push((Metric::ProcessCpu(process.pid), process.cpu_usage as f64));
+            samples.push((Metric::ProcessMemory(process.pid), process.memory as f64));
+        }
+
+        for (metric, value) in samples {
+            self.push(metric, Sample { time: now, value });
+        }
+        self.last_record = Some(now);
+
+        // PIDs are reused, so the history of a process ends with its details
+        let followed = followed.map(|process| process.pid);
+        let retention = self.config.retention;
+        let followed_cgroup = self.followed_cgroup.as_deref();
+        self.series.retain(|metric, series| {
+            if let Some(pid) = metric.process() {
+                return followed == Some(pid);
+            }
+            if metric.cgroup().is_some() && metric.cgroup() != followed_cgroup {
+                return false;
+            }
+            series
+                .latest()
+                .map(|latest| now.saturating_duration_since(latest.time) <= retention)
+                .unwrap_or(false)
+        });
+    }
+
+    fn push(&mut self, metric: Metric, sample: Sample) {
+        let config = self.config;
+        self.series.entry(metric).or_default().push(sample, &config);
+    }
+
+    /// Time of the last recorded snapshot
+    pub fn last_record(&self) -> Option<Instant> {
+        self.last_record
+    }
+
+    pub fn latest(&self, metric: &Metric) -> Option<f64> {
+        self.series
+            .get(metric)
+            .and_then(Series::latest)
+            .map(|sample| sample.value)
+    }
+
+    /// Returns the samples of a metric within `window` before the last record, oldest first
+    pub fn window(&self, metric: &Metric, window: Duration) -> Vec<Sample> {
+        match (self.series.get(metric), self.last_record) {
+            (Some(series), Some(now)) => series.window(window, now),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn stats(&self, metric: &Metric, window: Duration) -> Option<SeriesStats> {
+        let now = self.last_record?;
+        self.series.get(metric)?.stats(window, now)
+    }
+}
+
+/// Adds the avg10 pressure of every resource the kernel reports
+fn push_pressure(
+    samples: &mut Vec<(Metric, f64)>,
+    pressure: &ResourcePressure,
+    some: impl Fn(Resource) -> Metric,
+    full: impl Fn(Resource) -> Metric,
+) {
+    for resource in [Resource::Cpu, Resource::Memory, Resource::Io] {
+        if let Some(pressure) = pressure.get(resource) {
+            samples.push((some(resource), pressure.some.avg10));
+            if let Some(averages) = pressure.full {
+                samples.push((full(resource), averages.avg10));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HistoryConfig, Metric, MetricsHistory, Sample, Series};
+    use crate::core::{
+        CgroupPressure, NetworkSnapshot, Pressure, PressureAverages, ProcessDetailsSnapshot,
+        Resource, ResourcePressure, Snapshot,
+    };
+    use crate::ui::testing::snapshot;
+    use std::time::{Duration, Instant};
+    use sysinfo::{Pid, PidExt};
+
+    fn config() -> HistoryConfig {
+        HistoryConfig {
+            retention: Duration::from_secs(60),
+            full_resolution: Duration::from_secs(10),
+            downsample_interval: Duration::from_secs(5),
+        }
+    }
+
+    /// A series with one sample per second, each valued with its second
+    fn series(start: Instant, seconds: u64) -> Series {
+        let mut series = Series::default();
+        for second in 0..=seconds {
+            let sample = Sample {
+                time: start + Duration::from_secs(second),
+                value: second as f64,
+            };
+            series.push(sample, &config());
+        }
+        series
+    }
+
+    #[test]
+    fn averages_the_samples_older_than_the_full_resolution_into_buckets() {
+        let start = Instant::now();
+
+        // exactly at the boundary the oldest sample is still kept as recorded
+        let series_at_boundary = series(start, 10);
+        assert!(series_at_boundary.downsampled.is_empty());
+        assert!(series_at_boundary.bucket.is_none());
+        assert_eq!(series_at_boundary.samples.len(), 11);
+
+        // 0 to 5 expired: 0 to 4 are a complete bucket, 5 opened the next one
+        let series = series(start, 16);
+        let window: Vec<(f64, f64)> = series
+            .window(Duration::from_secs(3600), start + Duration::from_secs(16))
+            .iter()
+            .map(|sample| {
+                (
+                    sample.time.duration_since(start).as_secs_f64(),
+                    sample.value,
+                )
+            })
+            .collect();
+        let mut expected = vec![(0.0, 2.0), (5.0, 5.0)];
+        expected.extend((6..=16).map(|second| (second as f64, second as f64)));
+        assert_eq!(window, expected);
+    }
+
+    #[test]
+    fn weights_the_buckets_by_their_samples_in_the_statistics() {
+        let start = Instant::now();
+        let series = series(start, 16);
+
+        let stats = series
+            .stats(Duration::from_secs(3600), start + Duration::from_secs(16))
+            .unwrap();
+        assert_eq!(stats.min, 0.0);
+        assert_eq!(stats.max, 16.0);
+        assert_eq!(stats.avg, 8.0);
+    }
+
+    #[test]
+    fn drops_the_buckets_older_than_the_retention() {
+        let start = Instant::now();
+        let series = series(start, 80);
+
+        // the buckets starting at 20 to 60 are left, 65 is still being filled
+        assert_eq!(series.downsampled.len(), 9);
+        let oldest = series.window(Duration::from_secs(3600), start + Duration::from_secs(80))[0];
+        assert_eq!(oldest.time.duration_since(start), Duration::from_secs(20));
+        assert_eq!(oldest.value, 22.0);
+    }
+
+    #[test]
+    fn forgets_a_metric_once_it_was_not_recorded_for_the_retention() {
+        let start = Instant::now();
+        let mut history = MetricsHistory::new(config());
+        let received = Metric::NetworkReceived(String::from("eth0"));
+
+        let with_interface = Snapshot {
+            networks: vec![NetworkSnapshot {
+                name: String::from("eth0"),
+                ..NetworkSnapshot::default()
+            }],
+            ..Snapshot::default()
+        };
+        history.record(&with_interface, start);
+        history.record(&Snapshot::default(), start + Duration::from_secs(60));
+        assert_eq!(history.latest(&received), Some(0.0));
+
+        history.record(&Snapshot::default(), start + Duration::from_secs(61));
+        assert_eq!(history.latest(&received), None);
+    }
+
+    #[test]
+    fn follows_only_the_process_whose_details_are_open() {
+        let start = Instant::now();
+        let mut history = MetricsHistory::new(config());
+        let editor = Metric::ProcessCpu(Pid::from_u32(42));
+        let backup = Metric::ProcessCpu(Pid::from_u32(7));
+
+        history.record(&snapshot(), start);
+        assert_eq!(history.latest(&editor), None);
+
+        let mut with_details = snapshot();
+        with_details.process_details = Some(ProcessDetailsSnapshot {
+            pid: Pid::from_u32(42),
+            environment: Vec::new(),
+            threads: Ok(1),
+            limits: Ok(Vec::new()),
+            open_files: Ok(Vec::new()),
+            memory_maps: Ok(Vec::new()),
+        });
+        history.record(&with_details, start + Duration::from_secs(1));
+        assert_eq!(history.latest(&editor), Some(12.5));
+        assert_eq!(history.latest(&backup), None);
+
+        history.record(&snapshot(), start + Duration::from_secs(2));
+        assert_eq!(history.latest(&editor), None);
+    }
+
+    #[test]
+    fn follows_only_the_selected_control_group() {
+        let start = Instant::now();
+        let mut history = MetricsHistory::new(config());
+        let cgroup = |name: &str, avg10: f64| CgroupPressure {
+            name: name.to_string(),
+            pressure: ResourcePressure {
+                io: Some(Pressure {
+                    some: PressureAverages {
+                        avg10,
+                        ..PressureAverages::default()
+                    },
+                    full: None,
+                }),
+                ..ResourcePressure::default()
+            },
+        };
+        let snapshot = Snapshot {
+            cgroup_pressure: Some(vec![
+                cgroup("/system.slice", 4.5),
+                cgroup("/user.slice", 1.0),
+            ]),
+            ..Snapshot::default()
+        };
+        let system = Metric::CgroupPressureSome(String::from("/system.slice"), Resource::Io);
+        let user = Metric::CgroupPressureSome(String::from("/user.slice"), Resource::Io);
+
+        history.record(&snapshot, start);
+        assert_eq!(history.latest(&system), None);
+
+        history.follow_cgroup(Some(String::from("/system.slice")));
+        history.record(&snapshot, start + Duration::from_secs(1));
+        assert_eq!(history.latest(&system), Some(4.5));
+        assert_eq!(history.latest(&user), None);
+        assert_eq!(
+            history.latest(&Metric::CgroupPressureFull(
+                String::from("/system.slice"),
+                Resource::Io
+            )),
+            None
+        );
+
+        history.follow_cgroup(Some(String::from("/user.slice")));
+        history.record(&snapshot, start + Duration::from_secs(2));
+        assert_eq!(history.latest(&system), None);
+        assert_eq!(history.latest(&user), Some(1.0));
+    }
+}
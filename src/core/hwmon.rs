@@ -0,0 +1,216 @@
+use super::sysfs::{read_number, read_string};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Where the kernel exposes the hardware monitoring chips
+pub const HWMON_ROOT: &str = "/sys/class/hwmon";
+
+#[derive(Clone, Copy)]
+pub enum SensorKind {
+    Temperature,
+    Fan,
+    Voltage,
+    Current,
+    Power,
+}
+
+impl SensorKind {
+    /// Maps the prefix of a hwmon attribute (e.g. `temp` of `temp1_input`) to the kind of the sensor
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "temp" => Some(SensorKind::Temperature),
+            "fan" => Some(SensorKind::Fan),
+            "in" => Some(SensorKind::Voltage),
+            "curr" => Some(SensorKind::Current),
+            "power" => Some(SensorKind::Power),
+            _ => None,
+        }
+    }
+
+    /// hwmon reports milli-degrees, RPM, millivolts, milliamperes and microwatts
+    fn scale(&self) -> f64 {
+        match self {
+            SensorKind::Temperature | SensorKind::Voltage | SensorKind::Current => 1_000.0,
+            SensorKind::Fan => 1.0,
+            SensorKind::Power => 1_000_000.0,
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SensorKind::Temperature => "°C",
+            SensorKind::Fan => "RPM",
+            SensorKind::Voltage => "V",
+            SensorKind::Current => "A",
+            SensorKind::Power => "W",
+        }
+    }
+}
+
+/// A single reading of a hardware monitoring chip, converted to °C, RPM, V, A or W.
+#[derive(Clone)]
+pub struct Sensor {
+    pub kind: SensorKind,
+    pub label: String,
+    pub value: f64,
+    pub high: Option<f64>,
+    pub critical: Option<f64>,
+}
+
+/// A hardware monitoring chip (e.g. `coretemp`, `nvme` or `amdgpu`) with its sensors.
+#[derive(Clone)]
+pub struct SensorChip {
+    pub name: String,
+    pub sensors: Vec<Sensor>,
+}
+
+impl SensorChip {
+    /// Reads every chip below `root`, which is normally HWMON_ROOT.
+    /// Chips without any readable sensor are left out.
+    pub fn read_all(root: &Path) -> io::Result<Vec<Self>> {
+        let mut chips = Vec::new();
+
+        for entry in fs::read_dir(root)? {
+            let path = entry?.path();
+            if let Some(chip) = Self::read(&path) {
+                chips.push(chip);
+            }
+        }
+
+        chips.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(chips)
+    }
+
+    fn read(path: &Path) -> Option<Self> {
+        let name = read_string(&path.join("name")).unwrap_or_else(|| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+
+        let mut inputs: Vec<(SensorKind, String, u32, PathBuf)> = Vec::new();
+        for entry in fs::read_dir(path).ok()?.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            // power sensors provide an average instead of an input on some chips
+            let attribute = match file_name
+                .strip_suffix("_input")
+                .or_else(|| file_name.strip_suffix("_average"))
+            {
+                Some(attribute) => attribute,
+                None => continue,
+            };
+            let index_start = match attribute.find(|ch: char| ch.is_ascii_digit()) {
+                Some(index_start) => index_start,
+                None => continue,
+            };
+            let (prefix, index) = attribute.split_at(index_start);
+            if let (Some(kind), Ok(index)) = (SensorKind::from_prefix(prefix), index.parse()) {
+                // a power sensor may have both files, keep one of them
+                if !inputs.iter().any(|(_, other, _, _)| other == attribute) {
+                    inputs.push((kind, attribute.to_string(), index, entry.path()));
+                }
+            }
+        }
+        inputs.sort_by_key(|(kind, _, index, _)| (*kind as u8, *index));
+
+        let sensors: Vec<Sensor> = inputs
+            .into_iter()
+            .filter_map(|(kind, attribute, _, input_path)| {
+                let scaled = |file: &str| {
+                    read_number::<f64>(&path.join(format!("{}_{}", attribute, file)))
+                        .map(|value| value / kind.scale())
+                };
+                let value = read_number::<f64>(&input_path)? / kind.scale();
+
+                Some(Sensor {
+                    kind,
+                    label: read_string(&path.join(format!("{}_label", attribute)))
+                        .unwrap_or_else(|| attribute.clone()),
+                    value,
+                    high: scaled("max").or_else(|| scaled("cap")),
+                    critical: scaled("crit"),
+                })
+            })
+            .collect();
+
+        if sensors.is_empty() {
+            None
+        } else {
+            Some(SensorChip { name, sensors })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SensorChip;
+    use std::{fs, path::Path};
+
+    fn write_chip(root: &Path, directory: &str, attributes: &[(&str, &str)]) {
+        let directory = root.join(directory);
+        fs::create_dir(&directory).unwrap();
+        for (attribute, value) in attributes {
+            fs::write(directory.join(attribute), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn reads_the_chips_of_a_hwmon_directory() {
+        let root = tempfile::tempdir().unwrap();
+        write_chip(
+            root.path(),
+            "hwmon0",
+            &[
+                ("name", "coretemp"),
+                ("temp1_input", "45500"),
+                ("temp1_label", "Package id 0"),
+                ("temp1_max", "80000"),
+                ("temp1_crit", "100000"),
+                // without a label the attribute name is shown
+                ("temp2_input", "-1500"),
+                // a limit without an input is not a sensor
+                ("temp3_crit", "100000"),
+                // neither is an input which cannot be read
+                ("temp4_input", ""),
+                ("fan1_input", "1200"),
+                ("power1_average", "15250000"),
+                ("power1_cap", "28000000"),
+            ],
+        );
+        // a chip without a name file is named after its directory
+        write_chip(root.path(), "hwmon1", &[("in0_input", "1200")]);
+        // a chip without any input is left out
+        write_chip(root.path(), "hwmon2", &[("name", "acpitz")]);
+
+        let chips = SensorChip::read_all(root.path()).unwrap();
+
+        let names: Vec<&str> = chips.iter().map(|chip| chip.name.as_str()).collect();
+        assert_eq!(names, ["coretemp", "hwmon1"]);
+        let sensors = &chips[0].sensors;
+        assert_eq!(sensors.len(), 4);
+        assert_eq!(sensors[0].label, "Package id 0");
+        assert_eq!(sensors[0].value, 45.5);
+        assert_eq!(sensors[0].high, Some(80.0));
+        assert_eq!(sensors[0].critical, Some(100.0));
+        assert_eq!(sensors[1].label, "temp2");
+        assert_eq!(sensors[1].value, -1.5);
+        assert_eq!(sensors[1].critical, None);
+        assert_eq!(
+            (sensors[2].label.as_str(), sensors[2].value),
+            ("fan1", 1200.0)
+        );
+        assert_eq!(sensors[3].value, 15.25);
+        assert_eq!(sensors[3].high, Some(28.0));
+        assert_eq!(chips[1].sensors[0].value, 1.2);
+    }
+
+    #[test]
+    fn fails_without_the_hwmon_directory() {
+        let root = tempfile::tempdir().unwrap();
+
+        assert!(SensorChip::read_all(&root.path().join("missing")).is_err());
+        assert!(SensorChip::read_all(root.path()).unwrap().is_empty());
+    }
+}
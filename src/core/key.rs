@@ -0,0 +1,104 @@
+use std::{fmt, str::FromStr};
+
+/// A key press, independent of the terminal backend.
+/// Enter is reported as `Char('\n')` and Tab as `Char('\t')`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    Backspace,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    BackTab,
+    Delete,
+    Insert,
+    /// Function keys, F1 is `F(1)`
+    F(u8),
+    Char(char),
+    Alt(char),
+    Ctrl(char),
+    Esc,
+    /// Keys heimdal has no binding for
+    Unknown,
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    /// Parses the key names of the configuration, e.g. `q`, `Ctrl-c`, `F10` or `PageDown`.
+    /// Names are case-insensitive, except for single characters.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let single_char = |text: &str| {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(ch), None) => Some(ch),
+                _ => None,
+            }
+        };
+        if let Some(ch) = single_char(name) {
+            return Ok(Key::Char(ch));
+        }
+
+        let lowercase = name.to_ascii_lowercase();
+        let modified = |prefix: &str| {
+            lowercase
+                .strip_prefix(prefix)
+                .and_then(|_| single_char(&name[prefix.len()..]))
+        };
+        if let Some(ch) = modified("ctrl-") {
+            return Ok(Key::Ctrl(ch.to_ascii_lowercase()));
+        }
+        if let Some(ch) = modified("alt-") {
+            return Ok(Key::Alt(ch));
+        }
+        if let Some(number) = lowercase.strip_prefix('f').and_then(|n| n.parse().ok()) {
+            if (1..=12).contains(&number) {
+                return Ok(Key::F(number));
+            }
+        }
+
+        Ok(match lowercase.as_str() {
+            "space" => Key::Char(' '),
+            "enter" => Key::Char('\n'),
+            "tab" => Key::Char('\t'),
+            "backtab" | "shift-tab" => Key::BackTab,
+            "backspace" => Key::Backspace,
+            "esc" => Key::Esc,
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "home" => Key::Home,
+            "end" => Key::End,
+            "pageup" => Key::PageUp,
+            "pagedown" => Key::PageDown,
+            "delete" => Key::Delete,
+            "insert" => Key::Insert,
+            _ => return Err(format!("unknown key \"{}\"", name)),
+        })
+    }
+}
+
+/// Writes the name the key is parsed from
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::Char(' ') => write!(f, "Space"),
+            Key::Char('\n') => write!(f, "Enter"),
+            Key::Char('\t') => write!(f, "Tab"),
+            Key::Char(ch) => write!(f, "{}", ch),
+            Key::Ctrl(ch) => write!(f, "Ctrl-{}", ch),
+            Key::Alt(ch) => write!(f, "Alt-{}", ch),
+            Key::F(number) => write!(f, "F{}", number),
+            Key::BackTab => write!(f, "Shift-Tab"),
+            Key::PageUp => write!(f, "PageUp"),
+            Key::PageDown => write!(f, "PageDown"),
+            Key::Unknown => write!(f, "?"),
+            key => write!(f, "{:?}", key),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::{fs, io};
+
+const MEMINFO_PATH: &str = "/proc/meminfo";
+
+/// Memory statistics parsed from `/proc/meminfo`.
+/// All values are in kibibytes, as reported by the kernel.
+#[derive(Clone, Copy, Default)]
+pub struct MemInfo {
+    pub total: u64,
+    pub free: u64,
+    pub available: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    pub shared: u64,
+    pub slab: u64,
+    pub slab_reclaimable: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+    pub swap_cached: u64,
+    pub dirty: u64,
+    pub writeback: u64,
+}
+
+impl MemInfo {
+    /// Reads and parses the current contents of `/proc/meminfo`.
+    pub fn read() -> io::Result<Self> {
+        let content = fs::read_to_string(MEMINFO_PATH)?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Parses the contents of a meminfo file. Unknown or malformed lines are skipped.
+    pub fn parse(content: &str) -> Self {
+        let mut info = MemInfo::default();
+
+        for line in content.lines() {
+            let mut parts = line.split_whitespace();
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => (key, value),
+                _ => continue,
+            };
+            let value = match value.parse::<u64>() {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+
+            match key {
+                "MemTotal:" => info.total = value,
+                "MemFree:" => info.free = value,
+                "MemAvailable:" => info.available = value,
+                "Buffers:" => info.buffers = value,
+                "Cached:" => info.cached = value,
+                "Shmem:" => info.shared = value,
+                "Slab:" => info.slab = value,
+                "SReclaimable:" => info.slab_reclaimable = value,
+                "SwapTotal:" => info.swap_total = value,
+                "SwapFree:" => info.swap_free = value,
+                "SwapCached:" => info.swap_cached = value,
+                "Dirty:" => info.dirty = value,
+                "Writeback:" => info.writeback = value,
+                _ => (),
+            }
+        }
+
+        info
+    }
+
+    /// Memory in use by applications, computed the same way as `free` does.
+    pub fn used(&self) -> u64 {
+        self.total
+            .saturating_sub(self.free)
+            .saturating_sub(self.buffers)
+            .saturating_sub(self.cached)
+            .saturating_sub(self.slab_reclaimable)
+    }
+
+    pub fn swap_used(&self) -> u64 {
+        self.swap_total.saturating_sub(self.swap_free)
+    }
+
+    /// Percentage of the physical memory in use.
+    pub fn used_percent(&self) -> f64 {
+        percent_of(self.used(), self.total)
+    }
+
+    /// Percentage of the swap space in use.
+    pub fn swap_used_percent(&self) -> f64 {
+        percent_of(self.swap_used(), self.swap_total)
+    }
+}
+
+fn percent_of(value: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        value as f64 / total as f64 * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemInfo;
+
+    const MEMINFO: &str = "\
+MemTotal:       16303092 kB
+MemFree:         1846712 kB
+MemAvailable:    9135004 kB
+Buffers:          513872 kB
+Cached:          6923468 kB
+SwapCached:         1024 kB
+Active:          8201324 kB
+Inactive:        4904728 kB
+SwapTotal:       2097148 kB
+SwapFree:        1572861 kB
+Dirty:              1440 kB
+Writeback:             0 kB
+AnonPages:       5668112 kB
+Shmem:            604080 kB
+Slab:             633212 kB
+SReclaimable:     412888 kB
+SUnreclaim:       220324 kB
+HugePages_Total:       0
+Hugepagesize:       2048 kB
+";
+
+    #[test]
+    fn parses_a_meminfo_file() {
+        let info = MemInfo::parse(MEMINFO);
+
+        assert_eq!(info.total, 16_303_092);
+        assert_eq!(info.free, 1_846_712);
+        assert_eq!(info.available, 9_135_004);
+        assert_eq!(info.buffers, 513_872);
+        assert_eq!(info.cached, 6_923_468);
+        assert_eq!(info.shared, 604_080);
+        assert_eq!(info.slab, 633_212);
+        assert_eq!(info.slab_reclaimable, 412_888);
+        assert_eq!(info.swap_cached, 1_024);
+        assert_eq!(info.dirty, 1_440);
+        assert_eq!(info.writeback, 0);
+        // total - free - buffers - cached - reclaimable slab
+        assert_eq!(info.used(), 6_606_152);
+        assert_eq!(info.swap_used(), 524_287);
+        assert_eq!(info.swap_used_percent(), 25.0);
+    }
+
+    #[test]
+    fn leaves_missing_fields_at_zero() {
+        // an old kernel without MemAvailable and a machine without swap
+        let info = MemInfo::parse("MemTotal: 1000 kB\nMemFree: 400 kB\nSwapTotal: 0 kB\n");
+
+        assert_eq!(info.total, 1_000);
+        assert_eq!(info.available, 0);
+        assert_eq!(info.cached, 0);
+        assert_eq!(info.used_percent(), 60.0);
+        assert_eq!(info.swap_used_percent(), 0.0);
+        assert_eq!(MemInfo::parse("").used_percent(), 0.0);
+    }
+
+    #[test]
+    fn skips_truncated_and_malformed_lines() {
+        let info =
+            MemInfo::parse("MemTotal: 1000 kB\nMemFree:\nBuffers: 1x0 kB\nCached: 100 kB\nSlab");
+
+        assert_eq!(info.total, 1_000);
+        assert_eq!(info.free, 0);
+        assert_eq!(info.buffers, 0);
+        assert_eq!(info.cached, 100);
+        assert_eq!(info.slab, 0);
+    }
+}
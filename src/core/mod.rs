@@ -1,9 +1,55 @@
+mod collector;
+mod cpufreq;
+mod cpustat;
+mod diskstats;
+mod filesystem;
+mod history;
+mod hwmon;
+mod key;
+mod meminfo;
+mod netdev;
+mod power_supply;
+mod pressure;
+mod process_info;
+mod signal;
+mod snapshot;
+mod source;
+mod sysfs;
+
+pub use self::collector::{Collector, CollectorHandle};
+pub use self::cpufreq::{CpuFrequency, CPU_SYSFS_ROOT};
+pub use self::cpustat::{CpuBreakdown, CpuStat};
+pub use self::diskstats::DiskStat;
+pub use self::filesystem::InodeUsage;
+pub use self::history::{HistoryConfig, Metric, MetricsHistory, SeriesStats};
+pub use self::hwmon::{Sensor, SensorChip, SensorKind, HWMON_ROOT};
+pub use self::key::Key;
+pub use self::meminfo::MemInfo;
+pub use self::netdev::{operstate, NetDevStat};
+pub use self::power_supply::{Battery, PowerSupplies, POWER_SUPPLY_ROOT};
+pub use self::pressure::{CgroupPressure, Pressure, PressureAverages, Resource, ResourcePressure};
+pub use self::process_info::{
+    read_environment, read_limits, read_memory_maps, read_open_files, read_thread_count,
+    MemoryMapping, OpenFile, ProcessLimit,
+};
+pub use self::signal::{send_signal, signal_name, SIGNALS};
+pub use self::snapshot::{
+    CpuSnapshot, DiskSnapshot, HostSnapshot, MemorySnapshot, NetworkSnapshot, PowerSnapshot,
+    ProcessDetailsSnapshot, ProcessSnapshot, SensorsSnapshot, Snapshot,
+};
+#[cfg(test)]
+pub use self::source::{read_sensors, ScriptedSource};
+pub use self::source::{MetricsSource, SnapshotRequest, SysinfoSource};
+
 /// An application event.
-/// If within that tick rate no input event happens, the application will emit a Tick.
-/// Otherwise, the input will be emitted.
+/// The collector thread emits an Update every sampling interval, input is emitted as it happens.
 pub enum Event<I> {
     /// User input from keyboard
     Input(I),
-    ///
-    Tick,
+    /// The terminal was resized, the next frame must be drawn with the new size.
+    /// Only crossterm reports it, with termion the size is picked up by the next update.
+    #[cfg_attr(not(feature = "crossterm"), allow(dead_code))]
+    Resize,
+    /// Emitted periodically with the freshly collected system information
+    Update(Box<Snapshot>),
 }
@@ -0,0 +1,88 @@
+use std::{fs, io};
+
+const NETDEV_PATH: &str = "/proc/net/dev";
+const SYSFS_NET_PATH: &str = "/sys/class/net";
+
+/// Dropped packet counters of a network interface parsed from `/proc/net/dev`.
+/// `sysinfo` exposes every other counter of the file, but not these.
+#[derive(Clone, Default)]
+pub struct NetDevStat {
+    pub name: String,
+    pub rx_drops: u64,
+    pub tx_drops: u64,
+}
+
+impl NetDevStat {
+    /// Reads and parses the current contents of `/proc/net/dev`.
+    pub fn read_all() -> io::Result<Vec<Self>> {
+        let content = fs::read_to_string(NETDEV_PATH)?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Parses the contents of a net/dev file. The two header lines and malformed lines are skipped.
+    pub fn parse(content: &str) -> Vec<Self> {
+        content.lines().filter_map(Self::parse_line).collect()
+    }
+
+    fn parse_line(line: &str) -> Option<Self> {
+        let (name, counters) = line.split_once(':')?;
+        let counters: Vec<u64> = counters
+            .split_whitespace()
+            .map(|counter| counter.parse::<u64>().ok())
+            .collect::<Option<Vec<u64>>>()?;
+        if counters.len() < 16 {
+            return None;
+        }
+
+        Some(NetDevStat {
+            name: name.trim().to_string(),
+            rx_drops: counters[3],
+            tx_drops: counters[11],
+        })
+    }
+}
+
+/// Returns the operational state of an interface (e.g. "up", "down", "unknown")
+/// as reported by `/sys/class/net/<interface>/operstate`.
+pub fn operstate(interface: &str) -> Option<String> {
+    fs::read_to_string(format!("{}/{}/operstate", SYSFS_NET_PATH, interface))
+        .ok()
+        .map(|state| state.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NetDevStat;
+
+    const NETDEV: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo: 144016238   14556    0    0    0     0          0         0 144016238   14556    0    0    0     0       0          0
+  eth0: 2950382712 2437621    0   31    0     0          0     11297 192283311 1022143    0    7    0     0       0          0
+wlp3s0:  881237    4021    2    5    0     0          0         0   233411    1901    0    0    0     0       0          0
+";
+
+    #[test]
+    fn parses_a_net_dev_file() {
+        let stats = NetDevStat::parse(NETDEV);
+
+        let names: Vec<&str> = stats.iter().map(|stat| stat.name.as_str()).collect();
+        assert_eq!(names, ["lo", "eth0", "wlp3s0"]);
+        assert_eq!((stats[1].rx_drops, stats[1].tx_drops), (31, 7));
+        assert_eq!((stats[2].rx_drops, stats[2].tx_drops), (5, 0));
+    }
+
+    #[test]
+    fn skips_truncated_and_malformed_lines() {
+        let stats = NetDevStat::parse(
+            "  eth0: 2950382712 2437621    0   31    0     0          0     11297\n\
+             eth1: 100 1 0 x 0 0 0 0 200 2 0 0 0 0 0 0\n\
+             eth2 100 1 0 4 0 0 0 0 200 2 0 3 0 0 0 0\n\
+             eth3: 100 1 0 4 0 0 0 0 200 2 0 3 0 0 0 0\n",
+        );
+
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].name, "eth3");
+        assert_eq!((stats[0].rx_drops, stats[0].tx_drops), (4, 3));
+    }
+}
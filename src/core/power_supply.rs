@@ -0,0 +1,218 @@
+use super::sysfs::{read_number, read_string};
+use std::{fs, io, path::Path};
+
+/// Where the kernel exposes the batteries and power adapters
+pub const POWER_SUPPLY_ROOT: &str = "/sys/class/power_supply";
+
+/// State of a battery read from its power_supply directory.
+/// Energies are in Wh and the power in W. Batteries which report charge (µAh)
+/// instead of energy are converted with their voltage.
+#[derive(Clone)]
+pub struct Battery {
+    pub name: String,
+    /// e.g. "Charging", "Discharging", "Full" or "Not charging"
+    pub status: String,
+    pub capacity: Option<u8>,
+    pub energy_now: Option<f64>,
+    pub energy_full: Option<f64>,
+    pub energy_full_design: Option<f64>,
+    pub power: Option<f64>,
+    pub cycle_count: Option<u64>,
+}
+
+/// Batteries and the state of the AC adapters.
+#[derive(Clone, Default)]
+pub struct PowerSupplies {
+    pub batteries: Vec<Battery>,
+    /// None when there is no adapter, e.g. on desktops
+    pub ac_online: Option<bool>,
+}
+
+impl PowerSupplies {
+    /// Reads every power supply below `root`, which is normally POWER_SUPPLY_ROOT
+    pub fn read(root: &Path) -> io::Result<Self> {
+        let mut power_supplies = PowerSupplies::default();
+
+        for entry in fs::read_dir(root)? {
+            let path = entry?.path();
+            match read_string(&path.join("type")).as_deref() {
+                Some("Battery") => power_supplies.batteries.push(Battery::read(&path)),
+                Some("Mains") => {
+                    let online = read_number::<u8>(&path.join("online")).map(|online| online == 1);
+                    // any adapter plugged in powers the machine
+                    power_supplies.ac_online = match (power_supplies.ac_online, online) {
+                        (Some(true), _) => Some(true),
+                        (_, online) => online,
+                    };
+                }
+                _ => (),
+            }
+        }
+
+        power_supplies.batteries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(power_supplies)
+    }
+}
+
+impl Battery {
+    fn read(path: &Path) -> Self {
+        let value = |file: &str| read_number::<f64>(&path.join(file));
+        let voltage = value("voltage_now").map(|microvolts| microvolts / 1_000_000.0);
+        // µWh, or µAh multiplied with the voltage
+        let energy = |name: &str| {
+            value(&format!("energy_{}", name))
+                .map(|microwatt_hours| microwatt_hours / 1_000_000.0)
+                .or_else(|| {
+                    let charge = value(&format!("charge_{}", name))? / 1_000_000.0;
+                    Some(charge * voltage?)
+                })
+        };
+        let power = value("power_now")
+            .map(|microwatts| microwatts / 1_000_000.0)
+            .or_else(|| {
+                let current = value("current_now")? / 1_000_000.0;
+                Some(current * voltage?)
+            })
+            // some drivers report the discharge as a negative current
+            .map(f64::abs);
+
+        Battery {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            status: read_string(&path.join("status")).unwrap_or_else(|| String::from("Unknown")),
+            capacity: value("capacity").map(|capacity| capacity.clamp(0.0, 100.0) as u8),
+            energy_now: energy("now"),
+            energy_full: energy("full"),
+            energy_full_design: energy("full_design"),
+            power,
+            cycle_count: value("cycle_count").map(|count| count as u64),
+        }
+    }
+
+    pub fn is_discharging(&self) -> bool {
+        self.status == "Discharging"
+    }
+
+    /// Full capacity compared to the design capacity in percent
+    pub fn health_percent(&self) -> Option<f64> {
+        match (self.energy_full, self.energy_full_design) {
+            (Some(full), Some(design)) if design > 0.0 => Some(full / design * 100.0),
+            _ => None,
+        }
+    }
+
+    /// Estimated hours until the battery is empty when discharging or full when charging
+    pub fn hours_remaining(&self) -> Option<f64> {
+        let power = self.power.filter(|power| *power > 0.0)?;
+        let energy_now = self.energy_now?;
+
+        match self.status.as_str() {
+            "Discharging" => Some(energy_now / power),
+            "Charging" => Some((self.energy_full? - energy_now).max(0.0) / power),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PowerSupplies;
+    use std::{fs, path::Path};
+
+    fn write_supply(root: &Path, name: &str, attributes: &[(&str, &str)]) {
+        let directory = root.join(name);
+        fs::create_dir(&directory).unwrap();
+        for (attribute, value) in attributes {
+            fs::write(directory.join(attribute), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn reads_batteries_and_adapters() {
+        let root = tempfile::tempdir().unwrap();
+        // reports energy in µWh and power in µW
+        write_supply(
+            root.path(),
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("capacity", "80"),
+                ("energy_now", "40000000"),
+                ("energy_full", "50000000"),
+                ("energy_full_design", "62500000"),
+                ("power_now", "10000000"),
+                ("cycle_count", "312"),
+            ],
+        );
+        // reports charge in µAh and a negative current in µA
+        write_supply(
+            root.path(),
+            "BAT1",
+            &[
+                ("type", "Battery"),
+                ("status", "Charging"),
+                ("voltage_now", "12000000"),
+                ("charge_now", "2000000"),
+                ("charge_full", "4000000"),
+                ("current_now", "-500000"),
+            ],
+        );
+        write_supply(root.path(), "AC", &[("type", "Mains"), ("online", "0")]);
+        write_supply(root.path(), "ADP1", &[("type", "Mains"), ("online", "1")]);
+        write_supply(root.path(), "hidpp_battery_0", &[("type", "USB")]);
+
+        let power_supplies = PowerSupplies::read(root.path()).unwrap();
+
+        assert_eq!(power_supplies.ac_online, Some(true));
+        assert_eq!(power_supplies.batteries.len(), 2);
+        let energy = &power_supplies.batteries[0];
+        assert_eq!(energy.name, "BAT0");
+        assert!(energy.is_discharging());
+        assert_eq!(energy.capacity, Some(80));
+        assert_eq!(energy.energy_now, Some(40.0));
+        assert_eq!(energy.power, Some(10.0));
+        assert_eq!(energy.cycle_count, Some(312));
+        assert_eq!(energy.health_percent(), Some(80.0));
+        assert_eq!(energy.hours_remaining(), Some(4.0));
+
+        let charge = &power_supplies.batteries[1];
+        assert_eq!(charge.energy_now, Some(24.0));
+        assert_eq!(charge.energy_full, Some(48.0));
+        assert_eq!(charge.power, Some(6.0));
+        assert_eq!(charge.hours_remaining(), Some(4.0));
+    }
+
+    #[test]
+    fn leaves_missing_attributes_unknown() {
+        let root = tempfile::tempdir().unwrap();
+        write_supply(
+            root.path(),
+            "BAT0",
+            &[("type", "Battery"), ("capacity", "")],
+        );
+        // a charge without a voltage cannot be converted to energy
+        write_supply(
+            root.path(),
+            "BAT1",
+            &[("type", "Battery"), ("charge_now", "2000000")],
+        );
+
+        let power_supplies = PowerSupplies::read(root.path()).unwrap();
+
+        // a desktop has no adapter
+        assert_eq!(power_supplies.ac_online, None);
+        let battery = &power_supplies.batteries[0];
+        assert_eq!(battery.status, "Unknown");
+        assert_eq!(battery.capacity, None);
+        assert_eq!(battery.energy_now, None);
+        assert_eq!(battery.power, None);
+        assert_eq!(battery.health_percent(), None);
+        assert_eq!(battery.hours_remaining(), None);
+        assert_eq!(power_supplies.batteries[1].energy_now, None);
+
+        assert!(PowerSupplies::read(&root.path().join("missing")).is_err());
+    }
+}
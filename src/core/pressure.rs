@@ -0,0 +1,211 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const PROC_PRESSURE_ROOT: &str = "/proc/pressure";
+/// The cgroup v2 hierarchy, mounted below `unified` on hybrid setups
+const CGROUP_ROOTS: [&str; 2] = ["/sys/fs/cgroup/unified", "/sys/fs/cgroup"];
+
+/// Share of time in percent tasks were stalled over the last 10, 60 and 300 seconds,
+/// and the total stall time in microseconds.
+#[derive(Clone, Copy, Default)]
+pub struct PressureAverages {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    pub total: u64,
+}
+
+/// Pressure stall information of a resource.
+/// `some` counts the time at least one task was stalled, `full` the time all non-idle
+/// tasks were stalled at once. `full` is not reported for the CPU by older kernels.
+#[derive(Clone, Copy, Default)]
+pub struct Pressure {
+    pub some: PressureAverages,
+    pub full: Option<PressureAverages>,
+}
+
+impl Pressure {
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed pressure file"))
+    }
+
+    /// Parses `some avg10=0.00 avg60=0.00 avg300=0.00 total=0` lines
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut some = None;
+        let mut full = None;
+
+        for line in content.lines() {
+            let mut fields = line.split_whitespace();
+            let kind = fields.next();
+            let mut averages = PressureAverages::default();
+            for field in fields {
+                match field.split_once('=') {
+                    Some(("avg10", value)) => averages.avg10 = value.parse().ok()?,
+                    Some(("avg60", value)) => averages.avg60 = value.parse().ok()?,
+                    Some(("avg300", value)) => averages.avg300 = value.parse().ok()?,
+                    Some(("total", value)) => averages.total = value.parse().ok()?,
+                    _ => (),
+                }
+            }
+
+            match kind {
+                Some("some") => some = Some(averages),
+                Some("full") => full = Some(averages),
+                _ => (),
+            }
+        }
+
+        Some(Pressure { some: some?, full })
+    }
+}
+
+/// A resource the kernel reports the pressure of
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resource {
+    Cpu,
+    Memory,
+    Io,
+}
+
+/// Pressure of the CPU, memory and I/O, each None if the kernel does not provide it
+/// (PSI is disabled by default on some distributions).
+#[derive(Clone, Default)]
+pub struct ResourcePressure {
+    pub cpu: Option<Pressure>,
+    pub memory: Option<Pressure>,
+    pub io: Option<Pressure>,
+}
+
+impl ResourcePressure {
+    pub fn get(&self, resource: Resource) -> Option<&Pressure> {
+        match resource {
+            Resource::Cpu => self.cpu.as_ref(),
+            Resource::Memory => self.memory.as_ref(),
+            Resource::Io => self.io.as_ref(),
+        }
+    }
+
+    /// Reads the system-wide pressure from `/proc/pressure`
+    pub fn read_system() -> Self {
+        Self::read_files(Path::new(PROC_PRESSURE_ROOT), "")
+    }
+
+    /// Reads the `<resource><suffix>` files of a directory
+    fn read_files(directory: &Path, suffix: &str) -> Self {
+        let read = |resource: &str| {
+            Pressure::read(&directory.join(format!("{}{}", resource, suffix))).ok()
+        };
+
+        ResourcePressure {
+            cpu: read("cpu"),
+            memory: read("memory"),
+            io: read("io"),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.cpu.is_none() && self.memory.is_none() && self.io.is_none()
+    }
+}
+
+/// Pressure of a control group, read from its `cpu.pressure`, `memory.pressure` and `io.pressure`.
+#[derive(Clone)]
+pub struct CgroupPressure {
+    /// Path of the group relative to the cgroup root, `/` for the root group
+    pub name: String,
+    pub pressure: ResourcePressure,
+}
+
+impl CgroupPressure {
+    /// Reads the pressure of every control group in the cgroup v2 hierarchy.
+    /// Returns an empty list on cgroup v1 only systems.
+    pub fn read_all() -> Vec<Self> {
+        let root = match CGROUP_ROOTS
+            .iter()
+            .map(PathBuf::from)
+            .find(|root| root.join("cgroup.controllers").exists())
+        {
+            Some(root) => root,
+            None => return Vec::new(),
+        };
+
+        let mut cgroups = Vec::new();
+        let mut directories = vec![root.clone()];
+        while let Some(directory) = directories.pop() {
+            let pressure = ResourcePressure::read_files(&directory, ".pressure");
+            if !pressure.is_empty() {
+                let name = match directory.strip_prefix(&root) {
+                    Ok(relative) => format!("/{}", relative.display()),
+                    Err(_) => directory.display().to_string(),
+                };
+                cgroups.push(CgroupPressure { name, pressure });
+            }
+
+            if let Ok(entries) = fs::read_dir(&directory) {
+                directories.extend(
+                    entries
+                        .flatten()
+                        .filter(|entry| {
+                            entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false)
+                        })
+                        .map(|entry| entry.path()),
+                );
+            }
+        }
+
+        cgroups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pressure;
+
+    const MEMORY_PRESSURE: &str = "\
+some avg10=9.47 avg60=6.81 avg300=3.57 total=2237443965
+full avg10=1.25 avg60=0.50 avg300=0.10 total=48213
+";
+
+    #[test]
+    fn parses_a_pressure_file() {
+        let pressure = Pressure::parse(MEMORY_PRESSURE).unwrap();
+
+        assert_eq!(pressure.some.avg10, 9.47);
+        assert_eq!(pressure.some.avg60, 6.81);
+        assert_eq!(pressure.some.avg300, 3.57);
+        assert_eq!(pressure.some.total, 2_237_443_965);
+        let full = pressure.full.unwrap();
+        assert_eq!((full.avg10, full.avg60, full.avg300), (1.25, 0.5, 0.1));
+        assert_eq!(full.total, 48_213);
+    }
+
+    #[test]
+    fn accepts_a_cpu_pressure_file_without_full() {
+        // kernels before 5.13 report only `some` for the CPU
+        let pressure = Pressure::parse("some avg10=0.12 avg60=0.05 avg300=0.01 total=1024\n");
+
+        assert_eq!(pressure.unwrap().some.avg10, 0.12);
+        assert!(pressure.unwrap().full.is_none());
+    }
+
+    #[test]
+    fn rejects_files_without_some_or_with_malformed_values() {
+        assert!(Pressure::parse("").is_none());
+        assert!(Pressure::parse("full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n").is_none());
+        assert!(Pressure::parse("some avg10=high avg60=0.00 avg300=0.00 total=0\n").is_none());
+    }
+
+    #[test]
+    fn leaves_the_fields_of_truncated_lines_at_zero() {
+        let pressure = Pressure::parse("some avg10=4.00 avg60\nfull").unwrap();
+
+        assert_eq!(pressure.some.avg10, 4.0);
+        assert_eq!(pressure.some.avg60, 0.0);
+        assert_eq!(pressure.some.total, 0);
+        assert_eq!(pressure.full.unwrap().avg10, 0.0);
+    }
+}
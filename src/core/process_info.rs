@@ -0,0 +1,285 @@
+use std::{fs, io, path::PathBuf};
+use sysinfo::Pid;
+
+/// A resource limit of a process parsed from `/proc/<pid>/limits`.
+#[derive(Clone)]
+pub struct ProcessLimit {
+    pub name: String,
+    pub soft: String,
+    pub hard: String,
+    pub units: String,
+}
+
+/// An open file descriptor of a process and the file it points to.
+#[derive(Clone)]
+pub struct OpenFile {
+    pub fd: u32,
+    pub target: String,
+}
+
+/// A memory mapping of a process parsed from `/proc/<pid>/smaps`.
+/// Sizes are in kibibytes.
+#[derive(Clone, Default)]
+pub struct MemoryMapping {
+    pub address: String,
+    pub permissions: String,
+    /// The mapped file or a pseudo path like `[heap]`, empty for anonymous mappings
+    pub path: String,
+    pub size: u64,
+    pub rss: u64,
+    pub pss: u64,
+    pub swap: u64,
+}
+
+fn proc_path(pid: Pid, file: &str) -> PathBuf {
+    PathBuf::from(format!("/proc/{}/{}", pid, file))
+}
+
+/// Reads the environment variables of a process from `/proc/<pid>/environ`.
+pub fn read_environment(pid: Pid) -> io::Result<Vec<String>> {
+    let content = fs::read(proc_path(pid, "environ"))?;
+    Ok(content
+        .split(|byte| *byte == 0)
+        .filter(|variable| !variable.is_empty())
+        .map(|variable| String::from_utf8_lossy(variable).to_string())
+        .collect())
+}
+
+/// Reads the number of threads of a process from `/proc/<pid>/status`.
+pub fn read_thread_count(pid: Pid) -> io::Result<u64> {
+    let content = fs::read_to_string(proc_path(pid, "status"))?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("Threads:"))
+        .and_then(|threads| threads.trim().parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing thread count"))
+}
+
+/// Reads the resource limits of a process.
+pub fn read_limits(pid: Pid) -> io::Result<Vec<ProcessLimit>> {
+    let content = fs::read_to_string(proc_path(pid, "limits"))?;
+    Ok(parse_limits(&content))
+}
+
+/// Parses the contents of a limits file. The limit names contain spaces, so the
+/// columns are located by the positions of the titles in the header line.
+pub fn parse_limits(content: &str) -> Vec<ProcessLimit> {
+    let mut lines = content.lines();
+    let header = match lines.next() {
+        Some(header) => header,
+        None => return Vec::new(),
+    };
+    let (soft_start, hard_start, units_start) = match (
+        header.find("Soft Limit"),
+        header.find("Hard Limit"),
+        header.find("Units"),
+    ) {
+        (Some(soft), Some(hard), Some(units)) => (soft, hard, units),
+        _ => return Vec::new(),
+    };
+    let column = |line: &str, start: usize, end: usize| {
+        line.get(start..end.min(line.len()))
+            .unwrap_or("")
+            .trim()
+            .to_string()
+    };
+
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| ProcessLimit {
+            name: column(line, 0, soft_start),
+            soft: column(line, soft_start, hard_start),
+            hard: column(line, hard_start, units_start),
+            units: column(line, units_start, line.len()),
+        })
+        .collect()
+}
+
+/// Lists the open file descriptors of a process, ordered by descriptor number.
+pub fn read_open_files(pid: Pid) -> io::Result<Vec<OpenFile>> {
+    let mut open_files = Vec::new();
+
+    for entry in fs::read_dir(proc_path(pid, "fd"))? {
+        let entry = entry?;
+        let fd = match entry.file_name().to_string_lossy().parse::<u32>() {
+            Ok(fd) => fd,
+            Err(_) => continue,
+        };
+        // the descriptor may have been closed since the directory was listed
+        let target = match fs::read_link(entry.path()) {
+            Ok(target) => target.to_string_lossy().to_string(),
+            Err(_) => continue,
+        };
+        open_files.push(OpenFile { fd, target });
+    }
+
+    open_files.sort_by_key(|open_file| open_file.fd);
+    Ok(open_files)
+}
+
+/// Reads the memory mappings of a process.
+pub fn read_memory_maps(pid: Pid) -> io::Result<Vec<MemoryMapping>> {
+    let content = fs::read_to_string(proc_path(pid, "smaps"))?;
+    Ok(parse_smaps(&content))
+}
+
+/// Parses the contents of a smaps file. Every mapping starts with a header line
+/// (address, permissions, offset, device, inode and path) followed by `Key: value kB` lines.
+pub fn parse_smaps(content: &str) -> Vec<MemoryMapping> {
+    let mut mappings: Vec<MemoryMapping> = Vec::new();
+
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let first = match fields.next() {
+            Some(first) => first,
+            None => continue,
+        };
+
+        if let Some(key) = first.strip_suffix(':') {
+            let mapping = match mappings.last_mut() {
+                Some(mapping) => mapping,
+                None => continue,
+            };
+            let value = fields.next().and_then(|value| value.parse::<u64>().ok());
+            match (key, value) {
+                ("Size", Some(value)) => mapping.size = value,
+                ("Rss", Some(value)) => mapping.rss = value,
+                ("Pss", Some(value)) => mapping.pss = value,
+                ("Swap", Some(value)) => mapping.swap = value,
+                _ => (),
+            }
+        } else if first.contains('-') {
+            let permissions = fields.next().unwrap_or("").to_string();
+            // skip the offset, device and inode columns
+            let path = fields.skip(3).collect::<Vec<&str>>().join(" ");
+            mappings.push(MemoryMapping {
+                address: first.to_string(),
+                permissions,
+                path,
+                ..Default::default()
+            });
+        }
+    }
+
+    mappings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_limits, parse_smaps};
+
+    // the columns are padded with trailing spaces, as in the kernel's output
+    const LIMITS: &str = "\
+Limit                     Soft Limit           Hard Limit           Units     
+Max cpu time              unlimited            unlimited            seconds   
+Max file size             unlimited            unlimited            bytes     
+Max stack size            8388608              unlimited            bytes     
+Max open files            1024                 524288               files     
+Max nice priority         0                    0                    
+Max realtime timeout      unlimited            unlimited            us        
+";
+
+    #[test]
+    fn parses_a_limits_file() {
+        let limits = parse_limits(LIMITS);
+
+        assert_eq!(limits.len(), 6);
+        let open_files = &limits[3];
+        assert_eq!(open_files.name, "Max open files");
+        assert_eq!(open_files.soft, "1024");
+        assert_eq!(open_files.hard, "524288");
+        assert_eq!(open_files.units, "files");
+        assert_eq!(limits[1].soft, "unlimited");
+        // the priorities have no unit
+        assert_eq!(limits[4].name, "Max nice priority");
+        assert_eq!(limits[4].units, "");
+        assert_eq!(limits[5].units, "us");
+    }
+
+    #[test]
+    fn parses_truncated_limit_lines_and_rejects_unknown_headers() {
+        let limits = parse_limits(
+            "Limit                     Soft Limit           Hard Limit           Units\n\
+             Max cpu time              unlimited\n\
+             Max data\n",
+        );
+
+        assert_eq!(limits.len(), 2);
+        assert_eq!(limits[0].name, "Max cpu time");
+        assert_eq!(limits[0].soft, "unlimited");
+        assert_eq!(limits[0].hard, "");
+        assert_eq!(limits[1].name, "Max data");
+        assert_eq!(limits[1].soft, "");
+
+        assert!(parse_limits("").is_empty());
+        assert!(parse_limits("Limit Soft Hard\nMax cpu time 1 2\n").is_empty());
+    }
+
+    const SMAPS: &str = "\
+55e5a977f000-55e5a9781000 r--p 00000000 fe:00 317783                     /usr/bin/head
+Size:                  8 kB
+KernelPageSize:        4 kB
+Rss:                   8 kB
+Pss:                   4 kB
+Swap:                  0 kB
+VmFlags: rd mr mw me
+55e5aa2c4000-55e5aa2e5000 rw-p 00000000 00:00 0                          [heap]
+Size:                132 kB
+Rss:                  12 kB
+Pss:                  12 kB
+Swap:                 20 kB
+VmFlags: rd wr mr mw me ac
+7f3c1a200000-7f3c1a400000 rw-p 00000000 00:00 0
+Size:               2048 kB
+Rss:                1024 kB
+Pss:                 512 kB
+Swap:                  0 kB
+7f3c1a600000-7f3c1a601000 rw-s 00000000 00:01 1045                       /memfd:shared state (deleted)
+Size:                  4 kB
+Rss:                   4 kB
+";
+
+    #[test]
+    fn parses_a_smaps_file() {
+        let mappings = parse_smaps(SMAPS);
+
+        assert_eq!(mappings.len(), 4);
+        let binary = &mappings[0];
+        assert_eq!(binary.address, "55e5a977f000-55e5a9781000");
+        assert_eq!(binary.permissions, "r--p");
+        assert_eq!(binary.path, "/usr/bin/head");
+        assert_eq!(
+            (binary.size, binary.rss, binary.pss, binary.swap),
+            (8, 8, 4, 0)
+        );
+        assert_eq!(mappings[1].path, "[heap]");
+        assert_eq!(mappings[1].swap, 20);
+        // anonymous mappings have no path
+        assert_eq!(mappings[2].path, "");
+        assert_eq!(mappings[2].rss, 1024);
+        assert_eq!(mappings[3].path, "/memfd:shared state (deleted)");
+        assert_eq!(mappings[3].permissions, "rw-s");
+    }
+
+    #[test]
+    fn skips_truncated_and_malformed_smaps_lines() {
+        let mappings = parse_smaps(
+            "Rss: 100 kB\n\
+             7f3c1a200000-7f3c1a400000\n\
+             Size: 2048 kB\n\
+             Rss:\n\
+             Pss: many kB\n\
+             \n\
+             Swap: 8 kB\n",
+        );
+
+        assert_eq!(mappings.len(), 1);
+        let mapping = &mappings[0];
+        assert_eq!(mapping.permissions, "");
+        assert_eq!(mapping.path, "");
+        assert_eq!(
+            (mapping.size, mapping.rss, mapping.pss, mapping.swap),
+            (2048, 0, 0, 8)
+        );
+    }
+}
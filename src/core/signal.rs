@@ -0,0 +1,61 @@
+use std::io;
+use sysinfo::{Pid, PidExt, Signal};
+
+/// Signals offered for sending to a process, in the order they are listed in the signal menu.
+pub const SIGNALS: [Signal; 8] = [
+    Signal::Term,
+    Signal::Kill,
+    Signal::Hangup,
+    Signal::Interrupt,
+    Signal::Stop,
+    Signal::Continue,
+    Signal::User1,
+    Signal::User2,
+];
+
+/// Sends `signal` to the process identified by `pid`.
+/// Unlike `ProcessExt::kill_with`, this reports why the signal could not be delivered
+/// (e.g. `EPERM` when the process belongs to another user or `ESRCH` when it already exited).
+pub fn send_signal(pid: Pid, signal: Signal) -> io::Result<()> {
+    let pid = libc::pid_t::try_from(pid.as_u32())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+
+    // SAFETY: kill(2) does not access memory of the calling process.
+    let result = unsafe { libc::kill(pid, signal_number(signal)) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Returns the conventional name of a signal, e.g. "SIGTERM".
+pub fn signal_name(signal: Signal) -> &'static str {
+    match signal {
+        Signal::Term => "SIGTERM",
+        Signal::Kill => "SIGKILL",
+        Signal::Hangup => "SIGHUP",
+        Signal::Interrupt => "SIGINT",
+        Signal::Stop => "SIGSTOP",
+        Signal::Continue => "SIGCONT",
+        Signal::User1 => "SIGUSR1",
+        Signal::User2 => "SIGUSR2",
+        _ => "unsupported signal",
+    }
+}
+
+fn signal_number(signal: Signal) -> libc::c_int {
+    match signal {
+        Signal::Term => libc::SIGTERM,
+        Signal::Kill => libc::SIGKILL,
+        Signal::Hangup => libc::SIGHUP,
+        Signal::Interrupt => libc::SIGINT,
+        Signal::Stop => libc::SIGSTOP,
+        Signal::Continue => libc::SIGCONT,
+        Signal::User1 => libc::SIGUSR1,
+        Signal::User2 => libc::SIGUSR2,
+        // only the signals in SIGNALS are ever sent, but 0 would be harmless anyway:
+        // it just checks for the existence of the process
+        _ => 0,
+    }
+}
@@ -0,0 +1,147 @@
+use super::{
+    CgroupPressure, CpuFrequency, CpuStat, DiskStat, InodeUsage, MemInfo, MemoryMapping, OpenFile,
+    PowerSupplies, ProcessLimit, ResourcePressure, SensorChip,
+};
+use std::path::PathBuf;
+use sysinfo::Pid;
+
+/// The state of the system at one point in time, collected off the UI thread.
+/// Views only read snapshots, so rendering never waits for `/proc`.
+#[derive(Clone, Default)]
+pub struct Snapshot {
+    pub host: HostSnapshot,
+    pub cpu: CpuSnapshot,
+    pub memory: MemorySnapshot,
+    pub disks: Vec<DiskSnapshot>,
+    pub disk_stats: Vec<DiskStat>,
+    pub networks: Vec<NetworkSnapshot>,
+    pub processes: Vec<ProcessSnapshot>,
+    pub pressure: ResourcePressure,
+    /// Only collected while requested, walking the cgroup hierarchy is expensive
+    pub cgroup_pressure: Option<Vec<CgroupPressure>>,
+    pub sensors: SensorsSnapshot,
+    pub power: PowerSnapshot,
+    /// The `/proc` files of the process whose details are open
+    pub process_details: Option<ProcessDetailsSnapshot>,
+}
+
+#[derive(Clone, Default)]
+pub struct HostSnapshot {
+    pub os: String,
+    pub os_version: String,
+    pub kernel_version: String,
+    pub host_name: String,
+    /// Seconds since boot
+    pub uptime: u64,
+    /// Load average over 1, 5 and 15 minutes
+    pub load_average: [f64; 3],
+}
+
+#[derive(Clone, Default)]
+pub struct CpuSnapshot {
+    pub brand: String,
+    /// MHz
+    pub frequency: u64,
+    pub physical_core_count: Option<usize>,
+    /// Usage of all cores together in percent
+    pub usage: f32,
+    pub core_usages: Vec<f32>,
+    /// Temperatures of the cores in °C by sensor label
+    pub temperatures: Vec<(String, f32)>,
+    pub stat: CpuStat,
+    pub frequencies: Vec<CpuFrequency>,
+}
+
+/// sysinfo reports memory in kilobytes, `meminfo` is in kibibytes.
+#[derive(Clone, Default)]
+pub struct MemorySnapshot {
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub meminfo: MemInfo,
+}
+
+#[derive(Clone)]
+pub struct DiskSnapshot {
+    pub device: String,
+    pub file_system: String,
+    pub mount_point: PathBuf,
+    /// Bytes
+    pub total_space: u64,
+    pub available_space: u64,
+    pub inodes: Option<InodeUsage>,
+}
+
+/// Counters of a network interface. `received` and `transmitted` are
+/// the bytes since the previous snapshot, the others are totals since boot.
+#[derive(Clone, Default)]
+pub struct NetworkSnapshot {
+    pub name: String,
+    /// Operational state from sysfs, e.g. "up" or "down"
+    pub state: Option<String>,
+    pub received: u64,
+    pub transmitted: u64,
+    pub total_received: u64,
+    pub total_transmitted: u64,
+    pub total_packets_received: u64,
+    pub total_packets_transmitted: u64,
+    pub total_errors_on_received: u64,
+    pub total_errors_on_transmitted: u64,
+    pub rx_drops: u64,
+    pub tx_drops: u64,
+}
+
+#[derive(Clone)]
+pub struct ProcessSnapshot {
+    pub pid: Pid,
+    pub parent: Option<Pid>,
+    pub name: String,
+    pub command: Vec<String>,
+    pub executable: PathBuf,
+    pub working_directory: PathBuf,
+    pub user: String,
+    pub cpu_usage: f32,
+    /// Resident memory in kilobytes
+    pub memory: u64,
+    pub virtual_memory: u64,
+    pub status: String,
+    /// Seconds since the Unix epoch
+    pub start_time: u64,
+    /// Seconds
+    pub run_time: u64,
+}
+
+/// The hardware monitoring chips below `root`, or why they could not be listed
+#[derive(Clone, Default)]
+pub struct SensorsSnapshot {
+    pub root: PathBuf,
+    pub chips: Vec<SensorChip>,
+    pub error: Option<String>,
+}
+
+/// The batteries and AC adapters below `root`, or why they could not be listed
+#[derive(Clone, Default)]
+pub struct PowerSnapshot {
+    pub root: PathBuf,
+    pub supplies: PowerSupplies,
+    pub error: Option<String>,
+}
+
+/// The `/proc` files of a process which `sysinfo` does not read.
+/// They are read separately, so one of them being unreadable
+/// (e.g. a process of another user) does not hide the others.
+#[derive(Clone)]
+pub struct ProcessDetailsSnapshot {
+    pub pid: Pid,
+    pub environment: Vec<String>,
+    pub threads: Result<u64, String>,
+    pub limits: Result<Vec<ProcessLimit>, String>,
+    pub open_files: Result<Vec<OpenFile>, String>,
+    pub memory_maps: Result<Vec<MemoryMapping>, String>,
+}
+
+impl Snapshot {
+    pub fn process(&self, pid: Pid) -> Option<&ProcessSnapshot> {
+        self.processes.iter().find(|process| process.pid == pid)
+    }
+}
@@ -0,0 +1,248 @@
+use super::{
+    operstate, read_environment, read_limits, read_memory_maps, read_open_files, read_thread_count,
+    CgroupPressure, CpuFrequency, CpuSnapshot, CpuStat, DiskSnapshot, DiskStat, HostSnapshot,
+    InodeUsage, MemInfo, MemorySnapshot, NetDevStat, NetworkSnapshot, PowerSnapshot, PowerSupplies,
+    ProcessDetailsSnapshot, ProcessSnapshot, ResourcePressure, SensorChip, SensorsSnapshot,
+    Snapshot, CPU_SYSFS_ROOT, POWER_SUPPLY_ROOT,
+};
+#[cfg(test)]
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use sysinfo::{
+    ComponentExt, CpuExt, DiskExt, NetworkExt, NetworksExt, Pid, ProcessExt, System, SystemExt,
+    UserExt,
+};
+
+/// The readings which are only taken while a view shows them
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct SnapshotRequest {
+    /// The process whose `/proc` files are read
+    pub process_details: Option<Pid>,
+    pub cgroup_pressure: bool,
+}
+
+/// Where the collector thread takes its snapshots from.
+pub trait MetricsSource {
+    /// Samples the system. Called once per interval, so counters like the CPU usage
+    /// cover the time since the previous call.
+    fn collect(&mut self, request: &SnapshotRequest) -> Snapshot;
+}
+
+/// How many snapshots are taken before the list of mounted disks is read again.
+/// `refresh_disks` only updates the space of the disks found so far.
+const DISK_LIST_REFRESH_TICKS: u32 = 10;
+
+/// Reads the live system through `sysinfo` and the `/proc` and `/sys` parsers.
+pub struct SysinfoSource {
+    system: System,
+    /// Where the hardware monitoring chips are read from, normally HWMON_ROOT
+    hwmon_root: PathBuf,
+    /// Snapshots taken since the list of disks was last read
+    ticks_since_disk_list: u32,
+}
+
+impl SysinfoSource {
+    pub fn new(hwmon_root: PathBuf) -> Self {
+        SysinfoSource {
+            system: System::new_all(),
+            hwmon_root,
+            ticks_since_disk_list: 0,
+        }
+    }
+}
+
+impl MetricsSource for SysinfoSource {
+    fn collect(&mut self, request: &SnapshotRequest) -> Snapshot {
+        let system = &mut self.system;
+        system.refresh_cpu();
+        system.refresh_memory();
+        system.refresh_processes();
+        system.refresh_networks();
+        self.ticks_since_disk_list += 1;
+        if self.ticks_since_disk_list >= DISK_LIST_REFRESH_TICKS {
+            // picks up the file systems mounted or unmounted since
+            system.refresh_disks_list();
+            self.ticks_since_disk_list = 0;
+        }
+        system.refresh_disks();
+        system.refresh_components();
+        let system = &self.system;
+
+        let load_average = system.load_average();
+        let host = HostSnapshot {
+            os: system.name().unwrap_or_else(|| String::from("N/A")),
+            os_version: system.os_version().unwrap_or_else(|| String::from("N/A")),
+            kernel_version: system
+                .kernel_version()
+                .unwrap_or_else(|| String::from("N/A")),
+            host_name: system.host_name().unwrap_or_else(|| String::from("N/A")),
+            uptime: system.uptime(),
+            load_average: [load_average.one, load_average.five, load_average.fifteen],
+        };
+
+        let cpu = CpuSnapshot {
+            brand: system.global_cpu_info().brand().to_string(),
+            frequency: system.global_cpu_info().frequency(),
+            physical_core_count: system.physical_core_count(),
+            usage: system.global_cpu_info().cpu_usage(),
+            core_usages: system.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
+            temperatures: system
+                .components()
+                .iter()
+                .filter(|component| component.label().contains("Core"))
+                .map(|component| (component.label().to_string(), component.temperature()))
+                .collect(),
+            stat: CpuStat::read().unwrap_or_default(),
+            frequencies: CpuFrequency::read_all(Path::new(CPU_SYSFS_ROOT)).unwrap_or_default(),
+        };
+
+        let memory = MemorySnapshot {
+            total: system.total_memory(),
+            used: system.used_memory(),
+            available: system.available_memory(),
+            meminfo: MemInfo::read().unwrap_or_default(),
+        };
+
+        let disks = system
+            .disks()
+            .iter()
+            .map(|disk| DiskSnapshot {
+                device: disk.name().to_string_lossy().to_string(),
+                file_system: String::from_utf8_lossy(disk.file_system()).to_string(),
+                mount_point: disk.mount_point().to_path_buf(),
+                total_space: disk.total_space(),
+                available_space: disk.available_space(),
+                inodes: InodeUsage::for_mount_point(disk.mount_point()).ok(),
+            })
+            .collect();
+
+        let netdev_stats = NetDevStat::read_all().unwrap_or_default();
+        let mut networks: Vec<NetworkSnapshot> = system
+            .networks()
+            .iter()
+            .map(|(name, network)| {
+                let drops = netdev_stats.iter().find(|stat| &stat.name == name);
+                NetworkSnapshot {
+                    name: name.clone(),
+                    state: operstate(name),
+                    received: network.received(),
+                    transmitted: network.transmitted(),
+                    total_received: network.total_received(),
+                    total_transmitted: network.total_transmitted(),
+                    total_packets_received: network.total_packets_received(),
+                    total_packets_transmitted: network.total_packets_transmitted(),
+                    total_errors_on_received: network.total_errors_on_received(),
+                    total_errors_on_transmitted: network.total_errors_on_transmitted(),
+                    rx_drops: drops.map(|stat| stat.rx_drops).unwrap_or(0),
+                    tx_drops: drops.map(|stat| stat.tx_drops).unwrap_or(0),
+                }
+            })
+            .collect();
+        networks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let processes = system
+            .processes()
+            .values()
+            .map(|process| ProcessSnapshot {
+                pid: process.pid(),
+                parent: process.parent(),
+                name: process.name().to_string(),
+                command: process.cmd().to_vec(),
+                executable: process.exe().to_path_buf(),
+                working_directory: process.cwd().to_path_buf(),
+                user: process
+                    .user_id()
+                    .and_then(|uid| system.get_user_by_id(uid))
+                    .map(|user| user.name().to_string())
+                    .unwrap_or_else(|| String::from("N/A")),
+                cpu_usage: process.cpu_usage(),
+                memory: process.memory(),
+                virtual_memory: process.virtual_memory(),
+                status: process.status().to_string(),
+                start_time: process.start_time(),
+                run_time: process.run_time(),
+            })
+            .collect();
+
+        Snapshot {
+            host,
+            cpu,
+            memory,
+            disks,
+            disk_stats: DiskStat::read_all().unwrap_or_default(),
+            networks,
+            processes,
+            pressure: ResourcePressure::read_system(),
+            cgroup_pressure: request.cgroup_pressure.then(CgroupPressure::read_all),
+            sensors: read_sensors(&self.hwmon_root),
+            power: read_power_supplies(Path::new(POWER_SUPPLY_ROOT)),
+            process_details: request.process_details.map(read_process_details),
+        }
+    }
+}
+
+/// Reads the hardware monitoring chips below `root`, a failure is kept to be shown
+pub fn read_sensors(root: &Path) -> SensorsSnapshot {
+    let (chips, error) = match SensorChip::read_all(root) {
+        Ok(chips) => (chips, None),
+        Err(error) => (Vec::new(), Some(error.to_string())),
+    };
+    SensorsSnapshot {
+        root: root.to_path_buf(),
+        chips,
+        error,
+    }
+}
+
+fn read_power_supplies(root: &Path) -> PowerSnapshot {
+    let (supplies, error) = match PowerSupplies::read(root) {
+        Ok(supplies) => (supplies, None),
+        Err(error) => (PowerSupplies::default(), Some(error.to_string())),
+    };
+    PowerSnapshot {
+        root: root.to_path_buf(),
+        supplies,
+        error,
+    }
+}
+
+fn read_process_details(pid: Pid) -> ProcessDetailsSnapshot {
+    ProcessDetailsSnapshot {
+        pid,
+        environment: read_environment(pid).unwrap_or_default(),
+        threads: read_thread_count(pid).map_err(|error| error.to_string()),
+        limits: read_limits(pid).map_err(|error| error.to_string()),
+        open_files: read_open_files(pid).map_err(|error| error.to_string()),
+        memory_maps: read_memory_maps(pid).map_err(|error| error.to_string()),
+    }
+}
+
+/// Returns prepared snapshots in order and repeats the last one once the script is used up.
+#[cfg(test)]
+pub struct ScriptedSource {
+    snapshots: VecDeque<Snapshot>,
+}
+
+#[cfg(test)]
+impl ScriptedSource {
+    pub fn new(snapshots: Vec<Snapshot>) -> Self {
+        assert!(
+            !snapshots.is_empty(),
+            "the script needs at least one snapshot"
+        );
+        ScriptedSource {
+            snapshots: snapshots.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl MetricsSource for ScriptedSource {
+    fn collect(&mut self, _request: &SnapshotRequest) -> Snapshot {
+        if self.snapshots.len() > 1 {
+            self.snapshots.pop_front().unwrap()
+        } else {
+            self.snapshots[0].clone()
+        }
+    }
+}
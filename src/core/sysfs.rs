@@ -0,0 +1,14 @@
+use std::{fs, path::Path, str::FromStr};
+
+/// Reads a sysfs attribute without the trailing newline.
+/// Missing and unreadable attributes are None, as most of them are optional.
+pub(crate) fn read_string(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+}
+
+/// Reads a numeric sysfs attribute
+pub(crate) fn read_number<T: FromStr>(path: &Path) -> Option<T> {
+    read_string(path).and_then(|content| content.parse().ok())
+}
@@ -1,51 +1,140 @@
-use crate::core::Event;
+use crate::core::{Collector, CollectorHandle, Event, Key, SnapshotRequest, SysinfoSource};
 use std::{
-    io::stdin,
-    sync::mpsc::{self, Receiver, TryRecvError},
-    thread,
-    time::{Duration, Instant},
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, RecvError},
+    time::Duration,
 };
-use termion::event::Key;
-use termion::input::TermRead;
 
 pub struct Events {
     rx: Receiver<Event<Key>>,
+    collector: CollectorHandle,
 }
 
 impl Events {
-    pub fn new() -> Self {
+    /// Starts the input thread and the collector thread, which sends an update every `interval`
+    /// and reads the sensors below `hwmon_root`
+    pub fn new(interval: Duration, hwmon_root: PathBuf) -> Self {
         let (tx, rx) = mpsc::channel();
-        let input_tx = tx.clone();
 
         // Off-thread input event loop
+        input::spawn(tx.clone());
+        // Off-thread system information collection
+        let collector = Collector::new(SysinfoSource::new(hwmon_root)).spawn(tx, interval);
+
+        Events { rx, collector }
+    }
+
+    /// Changes the interval between two updates
+    pub fn set_interval(&self, interval: Duration) {
+        self.collector.set_interval(interval);
+    }
+
+    /// Changes what the next updates read on top of the system metrics
+    pub fn set_request(&self, request: SnapshotRequest) {
+        self.collector.set_request(request);
+    }
+
+    /// Waits for the next input or update
+    pub fn recv(&self) -> Result<Event<Key>, RecvError> {
+        self.rx.recv()
+    }
+}
+
+#[cfg(not(feature = "crossterm"))]
+mod input {
+    use crate::core::{Event, Key};
+    use std::{io::stdin, sync::mpsc::Sender, thread};
+    use termion::event::Key as TermionKey;
+    use termion::input::TermRead;
+
+    pub(super) fn spawn(tx: Sender<Event<Key>>) {
         thread::spawn(move || {
             let stdin = stdin();
 
             for key in stdin.keys().flatten() {
-                if let Err(error) = input_tx.send(Event::Input(key)) {
+                if let Err(error) = tx.send(Event::Input(convert(key))) {
                     eprint!("Error reading key from input: {}", error);
                 }
             }
         });
-        // Off-thread tick event loop
+    }
+
+    fn convert(key: TermionKey) -> Key {
+        match key {
+            TermionKey::Backspace => Key::Backspace,
+            TermionKey::Left => Key::Left,
+            TermionKey::Right => Key::Right,
+            TermionKey::Up => Key::Up,
+            TermionKey::Down => Key::Down,
+            TermionKey::Home => Key::Home,
+            TermionKey::End => Key::End,
+            TermionKey::PageUp => Key::PageUp,
+            TermionKey::PageDown => Key::PageDown,
+            TermionKey::BackTab => Key::BackTab,
+            TermionKey::Delete => Key::Delete,
+            TermionKey::Insert => Key::Insert,
+            TermionKey::F(number) => Key::F(number),
+            TermionKey::Char(ch) => Key::Char(ch),
+            TermionKey::Alt(ch) => Key::Alt(ch),
+            TermionKey::Ctrl(ch) => Key::Ctrl(ch),
+            TermionKey::Esc => Key::Esc,
+            _ => Key::Unknown,
+        }
+    }
+}
+
+#[cfg(feature = "crossterm")]
+mod input {
+    use crate::core::{Event, Key};
+    use crossterm::event::{self, KeyCode, KeyEvent, KeyModifiers, MouseEventKind};
+    use std::{sync::mpsc::Sender, thread};
+
+    pub(super) fn spawn(tx: Sender<Event<Key>>) {
         thread::spawn(move || {
-            let wait_time = Duration::from_secs(1);
-            let mut start = Instant::now();
-            loop {
-                let elapsed = start.elapsed();
-                if elapsed > wait_time {
-                    if let Err(error) = tx.send(Event::Tick) {
-                        eprint!("Error sending tick event: {}", error);
-                    }
-                    start = Instant::now();
+            while let Ok(event) = event::read() {
+                let event = match event {
+                    event::Event::Key(key) => Event::Input(convert(key)),
+                    // the wheel scrolls like the arrow keys
+                    event::Event::Mouse(mouse) => match mouse.kind {
+                        MouseEventKind::ScrollUp => Event::Input(Key::Up),
+                        MouseEventKind::ScrollDown => Event::Input(Key::Down),
+                        _ => continue,
+                    },
+                    event::Event::Resize(_, _) => Event::Resize,
+                };
+                if let Err(error) = tx.send(event) {
+                    eprint!("Error reading key from input: {}", error);
                 }
             }
         });
-        Events { rx }
     }
 
-    pub fn recv(&self) -> Result<Event<Key>, TryRecvError> {
-        self.rx.try_recv()
+    fn convert(key: KeyEvent) -> Key {
+        match key.code {
+            KeyCode::Char(ch) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Key::Ctrl(ch.to_ascii_lowercase())
+            }
+            KeyCode::Char(ch) if key.modifiers.contains(KeyModifiers::ALT) => Key::Alt(ch),
+            KeyCode::Char(ch) => Key::Char(ch),
+            KeyCode::Enter => Key::Char('\n'),
+            // some terminals report Shift-Tab as a Tab with the shift modifier
+            KeyCode::Tab if key.modifiers.contains(KeyModifiers::SHIFT) => Key::BackTab,
+            KeyCode::Tab => Key::Char('\t'),
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::BackTab => Key::BackTab,
+            KeyCode::Delete => Key::Delete,
+            KeyCode::Insert => Key::Insert,
+            KeyCode::F(number) => Key::F(number),
+            KeyCode::Esc => Key::Esc,
+            KeyCode::Null => Key::Unknown,
+        }
     }
 }
- 
\ No newline at end of file
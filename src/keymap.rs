@@ -0,0 +1,463 @@
+use crate::core::Key;
+use crate::ui::Tab;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// What a key press does. The views ignore the actions that have no meaning for them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Action {
+    Quit,
+    /// Opens the list of the key bindings
+    Help,
+    NextTab,
+    PreviousTab,
+    // switch to a tab
+    Overview,
+    Cpu,
+    Memory,
+    Processes,
+    Storage,
+    Network,
+    Sensors,
+    Battery,
+    Pressure,
+    // move the selection
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    /// Opens the details of the selected row
+    Select,
+    /// Clears the filter
+    Cancel,
+    Filter,
+    ReverseSort,
+    SortByPid,
+    SortByName,
+    SortByUser,
+    SortByCpu,
+    SortByMemory,
+    SortByStatus,
+    ToggleTree,
+    /// Collapses or expands the subtree of the selected process in tree mode
+    ToggleCollapse,
+    /// Expands the subtree of the selected process in tree mode
+    Expand,
+    /// Collapses the subtree of the selected process in tree mode
+    Collapse,
+    KillProcess,
+    /// Changes the length of the CPU history charts
+    CycleWindow,
+}
+
+impl Action {
+    /// The tab this action switches to
+    pub(crate) fn tab(self) -> Option<Tab> {
+        Some(match self {
+            Action::Overview => Tab::Overview,
+            Action::Cpu => Tab::CPU,
+            Action::Memory => Tab::Memory,
+            Action::Processes => Tab::Processes,
+            Action::Storage => Tab::Storage,
+            Action::Network => Tab::Network,
+            Action::Sensors => Tab::Sensors,
+            Action::Battery => Tab::Battery,
+            Action::Pressure => Tab::Pressure,
+            _ => return None,
+        })
+    }
+
+    /// The action which switches to `tab`
+    pub(crate) fn switch_to(tab: Tab) -> Action {
+        match tab {
+            Tab::Overview => Action::Overview,
+            Tab::CPU => Action::Cpu,
+            Tab::Memory => Action::Memory,
+            Tab::Processes => Action::Processes,
+            Tab::Storage => Action::Storage,
+            Tab::Network => Action::Network,
+            Tab::Sensors => Action::Sensors,
+            Tab::Battery => Action::Battery,
+            Tab::Pressure => Action::Pressure,
+        }
+    }
+
+    /// What the global actions do, for the help
+    pub(crate) fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "show this help",
+            Action::NextTab => "next tab",
+            Action::PreviousTab => "previous tab",
+            Action::Overview => "Overview tab",
+            Action::Cpu => "CPU tab",
+            Action::Memory => "Memory tab",
+            Action::Processes => "Processes tab",
+            Action::Storage => "Storage tab",
+            Action::Network => "Network tab",
+            Action::Sensors => "Sensors tab",
+            Action::Battery => "Battery tab",
+            Action::Pressure => "Pressure tab",
+            // the views describe what they do with the other actions
+            _ => "",
+        }
+    }
+
+    /// The key the views handle for this action, for the actions which move the selection
+    /// or answer a popup
+    pub(crate) fn navigation_key(self) -> Option<Key> {
+        Some(match self {
+            Action::Up => Key::Up,
+            Action::Down => Key::Down,
+            Action::Left => Key::Left,
+            Action::Right => Key::Right,
+            Action::PageUp => Key::PageUp,
+            Action::PageDown => Key::PageDown,
+            Action::Home => Key::Home,
+            Action::End => Key::End,
+            Action::Select => Key::Char('\n'),
+            Action::Cancel => Key::Esc,
+            _ => return None,
+        })
+    }
+}
+
+/// Built-in sets of bindings the configuration starts from
+#[derive(Copy, Clone, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Preset {
+    #[default]
+    Default,
+    /// hjkl to move, g/G to jump, Ctrl-u/Ctrl-d to page and K to kill
+    Vim,
+    /// Function keys and the sort keys of htop
+    Htop,
+}
+
+impl Preset {
+    fn bindings(self) -> Vec<(&'static str, Action)> {
+        let mut bindings = vec![
+            ("q", Action::Quit),
+            ("Ctrl-c", Action::Quit),
+            ("?", Action::Help),
+            ("Tab", Action::NextTab),
+            ("Shift-Tab", Action::PreviousTab),
+            ("Backspace", Action::PreviousTab),
+            ("o", Action::Overview),
+            ("c", Action::Cpu),
+            ("m", Action::Memory),
+            ("p", Action::Processes),
+            ("s", Action::Storage),
+            ("n", Action::Network),
+            ("e", Action::Sensors),
+            ("b", Action::Battery),
+            ("u", Action::Pressure),
+            ("Up", Action::Up),
+            ("Down", Action::Down),
+            ("Left", Action::Left),
+            ("Right", Action::Right),
+            ("PageUp", Action::PageUp),
+            ("PageDown", Action::PageDown),
+            ("Home", Action::Home),
+            ("End", Action::End),
+            ("Enter", Action::Select),
+            ("Esc", Action::Cancel),
+            ("/", Action::Filter),
+            ("r", Action::ReverseSort),
+            ("t", Action::ToggleTree),
+            ("Space", Action::ToggleCollapse),
+            ("+", Action::Expand),
+            ("-", Action::Collapse),
+            ("k", Action::KillProcess),
+            ("w", Action::CycleWindow),
+        ];
+
+        match self {
+            Preset::Default => (),
+            Preset::Vim => bindings.extend([
+                ("k", Action::Up),
+                ("j", Action::Down),
+                ("h", Action::Left),
+                ("l", Action::Right),
+                ("g", Action::Home),
+                ("G", Action::End),
+                ("Ctrl-u", Action::PageUp),
+                ("Ctrl-d", Action::PageDown),
+                ("K", Action::KillProcess),
+            ]),
+            Preset::Htop => bindings.extend([
+                ("F1", Action::Help),
+                ("F3", Action::Filter),
+                ("F4", Action::Filter),
+                ("F5", Action::ToggleTree),
+                ("F9", Action::KillProcess),
+                ("F10", Action::Quit),
+                ("I", Action::ReverseSort),
+                ("P", Action::SortByCpu),
+                ("M", Action::SortByMemory),
+                ("N", Action::SortByPid),
+            ]),
+        }
+        bindings
+    }
+}
+
+/// An entry of the key legend: an action and what it does in the view,
+/// or a key which is not rebindable, like those of the popups
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeyHint {
+    Action(Action, &'static str),
+    Key(&'static str, &'static str),
+}
+
+/// One key name or a list of them
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum KeyNames {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyNames {
+    fn names(&self) -> &[String] {
+        match self {
+            KeyNames::One(name) => std::slice::from_ref(name),
+            KeyNames::Many(names) => names,
+        }
+    }
+}
+
+/// The `[keys]` table of the configuration: a preset and the actions bound to other keys
+#[derive(Clone, Default, Deserialize)]
+pub(crate) struct KeyConfig {
+    #[serde(default)]
+    pub preset: Preset,
+    #[serde(flatten)]
+    pub bindings: HashMap<Action, KeyNames>,
+}
+
+/// Maps the key presses to actions
+#[derive(Clone)]
+pub(crate) struct Keymap {
+    bindings: HashMap<Key, Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::new(&KeyConfig::default()).expect("the presets are valid")
+    }
+}
+
+impl Keymap {
+    /// Starts from the preset, an action bound in the configuration loses the keys of the preset.
+    /// Fails on unknown key names and on keys bound to several actions.
+    pub(crate) fn new(config: &KeyConfig) -> Result<Self, String> {
+        let mut bindings = HashMap::new();
+        for (name, action) in config.preset.bindings() {
+            bindings.insert(name.parse()?, action);
+        }
+        bindings.retain(|_, action| !config.bindings.contains_key(action));
+
+        // sorted, so the errors do not depend on the order of the hash map
+        let mut configured: Vec<(String, Action, &KeyNames)> = config
+            .bindings
+            .iter()
+            .map(|(action, names)| (action_name(*action), *action, names))
+            .collect();
+        configured.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut bound: HashMap<Key, Action> = HashMap::new();
+        for (name, action, names) in configured {
+            for key_name in names.names() {
+                let key: Key = key_name
+                    .parse()
+                    .map_err(|error| format!("keys.{}: {}", name, error))?;
+                match bound
+                    .insert(key, action)
+                    .or_else(|| bindings.get(&key).copied())
+                {
+                    Some(other) if other != action => {
+                        return Err(format!(
+                            "the key '{}' is bound more than once, to {} and {}",
+                            key,
+                            action_name(other),
+                            name
+                        ));
+                    }
+                    _ => (),
+                }
+            }
+        }
+        bindings.extend(bound);
+
+        Ok(Keymap { bindings })
+    }
+
+    /// Returns the action of a key. Letters which are not bound in upper case
+    /// do what they do in lower case.
+    pub(crate) fn action(&self, key: Key) -> Option<Action> {
+        self.bindings.get(&key).copied().or_else(|| match key {
+            Key::Char(ch) if ch.is_uppercase() => self
+                .bindings
+                .get(&Key::Char(ch.to_ascii_lowercase()))
+                .copied(),
+            _ => None,
+        })
+    }
+
+    /// Keys bound to an action, single characters first, then sorted by their names
+    pub(crate) fn keys(&self, action: Action) -> Vec<Key> {
+        let mut keys: Vec<Key> = self
+            .bindings
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(key, _)| *key)
+            .collect();
+        keys.sort_by_key(|key| {
+            let name = key.to_string();
+            (name.chars().count() > 1, name)
+        });
+        keys
+    }
+
+    /// The key and the description of a legend entry, None if the action has no key
+    pub(crate) fn hint(&self, hint: KeyHint) -> Option<(String, &'static str)> {
+        match hint {
+            KeyHint::Action(action, description) => self
+                .keys(action)
+                .first()
+                .map(|key| (key.to_string(), description)),
+            KeyHint::Key(key, description) => Some((key.to_string(), description)),
+        }
+    }
+
+    /// Translates the keys bound to a navigation action to the key the popups handle,
+    /// so the bindings also move through the menus
+    pub(crate) fn navigation_key(&self, key: Key) -> Key {
+        self.action(key)
+            .and_then(Action::navigation_key)
+            .unwrap_or(key)
+    }
+}
+
+/// The name of an action in the configuration
+fn action_name(action: Action) -> String {
+    let debug = format!("{:?}", action);
+    let mut name = String::new();
+    for (index, ch) in debug.char_indices() {
+        if ch.is_uppercase() && index > 0 {
+            name.push('_');
+        }
+        name.push(ch.to_ascii_lowercase());
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, KeyConfig, KeyHint, Keymap};
+    use crate::core::Key;
+
+    fn keymap(text: &str) -> Result<Keymap, String> {
+        let config: KeyConfig = toml::from_str(text).map_err(|error| error.to_string())?;
+        Keymap::new(&config)
+    }
+
+    #[test]
+    fn parses_and_names_keys() {
+        for (name, key) in [
+            ("q", Key::Char('q')),
+            ("Q", Key::Char('Q')),
+            ("Ctrl-c", Key::Ctrl('c')),
+            ("Alt-x", Key::Alt('x')),
+            ("F10", Key::F(10)),
+            ("Space", Key::Char(' ')),
+            ("Enter", Key::Char('\n')),
+            ("Shift-Tab", Key::BackTab),
+            ("PageDown", Key::PageDown),
+        ] {
+            assert_eq!(name.parse::<Key>(), Ok(key));
+            assert_eq!(key.to_string(), name);
+        }
+        assert_eq!("ctrl-C".parse::<Key>(), Ok(Key::Ctrl('c')));
+        assert_eq!("backtab".parse::<Key>(), Ok(Key::BackTab));
+        assert!("F13".parse::<Key>().is_err());
+        assert!("Ctrl-".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn the_default_preset_keeps_the_usual_keys() {
+        let keymap = Keymap::default();
+
+        assert_eq!(keymap.action(Key::Char('q')), Some(Action::Quit));
+        assert_eq!(keymap.action(Key::Ctrl('c')), Some(Action::Quit));
+        assert_eq!(keymap.action(Key::Char('\t')), Some(Action::NextTab));
+        assert_eq!(keymap.action(Key::BackTab), Some(Action::PreviousTab));
+        // letters work in both cases unless the upper case one is bound
+        assert_eq!(keymap.action(Key::Char('P')), Some(Action::Processes));
+        assert_eq!(keymap.action(Key::Char('k')), Some(Action::KillProcess));
+        assert_eq!(keymap.action(Key::Char('j')), None);
+        assert_eq!(keymap.action(Key::Char(' ')), Some(Action::ToggleCollapse));
+        assert_eq!(keymap.action(Key::Char('+')), Some(Action::Expand));
+        assert_eq!(keymap.action(Key::Char('-')), Some(Action::Collapse));
+    }
+
+    #[test]
+    fn presets_add_their_bindings() {
+        let vim = keymap("preset = \"vim\"").unwrap();
+        assert_eq!(vim.action(Key::Char('j')), Some(Action::Down));
+        assert_eq!(vim.action(Key::Char('k')), Some(Action::Up));
+        assert_eq!(vim.action(Key::Char('K')), Some(Action::KillProcess));
+        assert_eq!(vim.action(Key::Ctrl('d')), Some(Action::PageDown));
+        assert_eq!(vim.navigation_key(Key::Char('j')), Key::Down);
+        assert_eq!(vim.navigation_key(Key::Char('y')), Key::Char('y'));
+        assert_eq!(
+            vim.hint(KeyHint::Action(Action::Down, "next")),
+            Some((String::from("j"), "next"))
+        );
+        assert_eq!(vim.hint(KeyHint::Action(Action::SortByCpu, "cpu")), None);
+
+        let htop = keymap("preset = \"htop\"").unwrap();
+        assert_eq!(htop.action(Key::F(10)), Some(Action::Quit));
+        assert_eq!(htop.action(Key::Char('P')), Some(Action::SortByCpu));
+        assert_eq!(htop.action(Key::Char('p')), Some(Action::Processes));
+        assert_eq!(
+            htop.keys(Action::Filter),
+            [Key::Char('/'), Key::F(3), Key::F(4)]
+        );
+        assert_eq!(
+            htop.keys(Action::Quit),
+            [Key::Char('q'), Key::Ctrl('c'), Key::F(10)]
+        );
+        assert_eq!(htop.keys(Action::Help), [Key::Char('?'), Key::F(1)]);
+    }
+
+    #[test]
+    fn configured_keys_replace_the_keys_of_the_preset() {
+        let keymap = keymap("kill_process = \"x\"\nup = [\"k\", \"Up\"]").unwrap();
+
+        assert_eq!(keymap.action(Key::Char('x')), Some(Action::KillProcess));
+        assert_eq!(keymap.action(Key::Char('k')), Some(Action::Up));
+        assert_eq!(keymap.action(Key::Up), Some(Action::Up));
+        assert_eq!(keymap.keys(Action::KillProcess), [Key::Char('x')]);
+    }
+
+    #[test]
+    fn rejects_invalid_bindings() {
+        let error = |text: &str| match keymap(text) {
+            Err(error) => error,
+            Ok(_) => panic!("{:?} was accepted", text),
+        };
+
+        assert!(error("quit = \"k\"").contains("'k' is bound more than once"));
+        assert!(error("quit = \"x\"\ncpu = \"x\"").contains("to cpu and quit"));
+        assert!(error("quit = \"Meta-q\"").contains("keys.quit: unknown key"));
+        assert!(error("fly = \"f\"").contains("unknown variant `fly`"));
+        assert!(error("preset = \"emacs\"").contains("emacs"));
+    }
+}
@@ -1,33 +1,59 @@
 mod app;
+mod cli;
+mod config;
 mod core;
 mod events;
+mod keymap;
 mod ui;
 
-use crate::core::Event;
+use crate::core::{Event, Key};
 use app::App;
+use clap::Parser;
+use cli::Args;
+use config::{Config, ConfigWatcher};
 use events::Events;
+use keymap::Action;
 use std::time::Duration;
-use std::{error::Error, io};
-use termion::event::Key;
-use termion::raw::IntoRawMode;
-use tui::{backend::TermionBackend, Terminal};
+use std::{error::Error, io, process};
+use tui::{backend::Backend, Terminal};
+use ui::{ColorBackend, ColorDepth, ColorMode};
 
-/// Emit a tick event by every 200 ms (60fps).
-/// You can tune the responsiveness of the application.
-/// But setting it too low also means that this loop will run a lot and eat up resources.
-pub const DEFAULT_TICK_RATE: u64 = 200;
+#[cfg(not(any(feature = "termion", feature = "crossterm")))]
+compile_error!("heimdal needs a terminal backend, enable the `termion` or `crossterm` feature");
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let stdout = io::stdout().into_raw_mode()?;
-    let backend = TermionBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let args = Args::parse();
+    let watcher = ConfigWatcher::new(args.config.clone().or_else(config::default_path));
+    let config = match Config::load(watcher.path(), &args) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("heimdal: {}", error);
+            process::exit(1);
+        }
+    };
+
+    let mut terminal = setup_terminal(config.color, config.color_depth)?;
+    let _ = terminal.clear();
+
+    let result = run(&mut terminal, &args, &config, watcher);
+
     let _ = terminal.clear();
+    restore_terminal()?;
+    result
+}
 
-    //TODO: tick_rate could be set from the args and/or from a cfg file
-    let tick_rate = Duration::from_millis(DEFAULT_TICK_RATE);
-    let mut should_update = false;
-    let events = Events::new();
-    let mut app = App::new();
+fn run<B: Backend>(
+    terminal: &mut Terminal<ColorBackend<B>>,
+    args: &Args,
+    config: &Config,
+    mut watcher: ConfigWatcher,
+) -> Result<(), Box<dyn Error>> {
+    let events = Events::new(
+        Duration::from_millis(config.tick_rate),
+        config.hwmon_root.clone(),
+    );
+    let mut app = App::new(config);
+    app.filter_processes(&args.pids, args.user.clone());
 
     loop {
         // render the current state of the terminal on the main thread
@@ -39,20 +65,92 @@ fn main() -> Result<(), Box<dyn Error>> {
         if let Ok(event) = events.recv() {
             match event {
                 Event::Input(key) => match key {
-                    Key::Char('q') => break,
-                    // termion does not have a separate Tab Key like Backspace, it handled as a '\t' char
-                    termion::event::Key::Backspace => app.previous_tab(),
-                    Key::Up | Key::Down | Key::Left | Key::Right => app.handle_arrow_keys(key),
-                    Key::Char(ch) => app.switch_tab(ch),
-                    _ => (),
+                    // Ctrl-C always quits, even while a popup is open
+                    Key::Ctrl('c') => break,
+                    _ if app.is_modal() => app.handle_modal_key(key),
+                    _ => match app.action(key) {
+                        Some(Action::Quit) => break,
+                        Some(action) => app.handle_action(action),
+                        None => (),
+                    },
                 },
-                Event::Tick => {
-                    app.tick();
+                // the next draw picks up the new size
+                Event::Resize => (),
+                Event::Update(snapshot) => {
+                    app.update(*snapshot);
+
+                    // the configuration is reloaded as soon as it is saved, an invalid one is reported
+                    // and the current settings are kept
+                    if watcher.changed() {
+                        match Config::load(watcher.path(), args) {
+                            Ok(config) => {
+                                events.set_interval(Duration::from_millis(config.tick_rate));
+                                terminal
+                                    .backend_mut()
+                                    .set_mode(config.color, config.color_depth);
+                                terminal.clear()?;
+                                app.configure(&config);
+                            }
+                            Err(error) => app.show_config_error(error.to_string()),
+                        }
+                    }
                 }
             }
         }
+
+        // the next snapshot reads what the active view needs
+        events.set_request(app.snapshot_request());
     }
 
-    let _ = terminal.clear();
-    return Ok(());
+    Ok(())
+}
+
+/// Switches stdout to raw mode, it is restored when the terminal is dropped
+#[cfg(not(feature = "crossterm"))]
+fn setup_terminal(
+    colors: ColorMode,
+    depth: ColorDepth,
+) -> Result<Terminal<ColorBackend<impl Backend>>, Box<dyn Error>> {
+    use termion::raw::IntoRawMode;
+    use tui::backend::TermionBackend;
+
+    let stdout = io::stdout().into_raw_mode()?;
+    let backend = ColorBackend::new(TermionBackend::new(stdout), colors, depth);
+    Ok(Terminal::new(backend)?)
+}
+
+#[cfg(not(feature = "crossterm"))]
+fn restore_terminal() -> Result<(), Box<dyn Error>> {
+    Ok(())
+}
+
+/// Switches the terminal to raw mode and captures the mouse for scrolling
+#[cfg(feature = "crossterm")]
+fn setup_terminal(
+    colors: ColorMode,
+    depth: ColorDepth,
+) -> Result<Terminal<ColorBackend<impl Backend>>, Box<dyn Error>> {
+    use crossterm::{event::EnableMouseCapture, execute, terminal::enable_raw_mode};
+    use tui::backend::CrosstermBackend;
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    let terminal = execute!(stdout, EnableMouseCapture).and_then(|()| {
+        let backend = ColorBackend::new(CrosstermBackend::new(stdout), colors, depth);
+        Terminal::new(backend)
+    });
+    // the shell must not be left in raw mode when the terminal could not be set up
+    if terminal.is_err() {
+        let _ = restore_terminal();
+    }
+    Ok(terminal?)
+}
+
+#[cfg(feature = "crossterm")]
+fn restore_terminal() -> Result<(), Box<dyn Error>> {
+    use crossterm::{event::DisableMouseCapture, execute, terminal::disable_raw_mode};
+
+    execute!(io::stdout(), DisableMouseCapture)?;
+    disable_raw_mode()?;
+    Ok(())
 }
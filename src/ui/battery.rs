@@ -0,0 +1,185 @@
+use super::{format_duration, window_points, DisplaySettings, View, ViewContext, HISTORY_WINDOW};
+use crate::core::{Battery, Metric, MetricsHistory};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    symbols,
+    text::{Span, Spans, Text},
+    widgets::{Axis, Block, BorderType, Borders, Chart, Dataset, Gauge, GraphType, Paragraph},
+    Frame,
+};
+
+pub struct BatteryView;
+
+impl BatteryView {
+    pub fn new() -> Self {
+        BatteryView
+    }
+
+    fn render_battery_state<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        battery: &Battery,
+        ac_online: Option<bool>,
+        area: Rect,
+        settings: &DisplaySettings,
+    ) {
+        let block = Block::default()
+            .title(battery.name.clone())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+        let state_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1)].as_ref())
+            .margin(1)
+            .split(area);
+
+        let capacity = battery.capacity.unwrap_or(0) as u16;
+        // an empty battery is the critical state, unlike a full disk
+        let charge_bar = Gauge::default()
+            .percent(capacity)
+            .label(format!("{}% {}", capacity, battery.status))
+            .gauge_style(Style::default().fg(settings.color_for_percent(100 - capacity)));
+
+        let watt_hours = |energy: Option<f64>| match energy {
+            Some(energy) => format!("{:.1} Wh", energy),
+            None => String::from("N/A"),
+        };
+        let remaining = match battery.hours_remaining() {
+            Some(hours) if battery.is_discharging() => {
+                format!("{} until empty", format_duration((hours * 3_600.0) as u64))
+            }
+            Some(hours) => format!("{} until full", format_duration((hours * 3_600.0) as u64)),
+            None => String::from("N/A"),
+        };
+        let ac_adapter = match ac_online {
+            Some(true) => "online",
+            Some(false) => "offline",
+            None => "N/A",
+        };
+
+        let state_text = Text::from(vec![
+            state_line("AC adapter", ac_adapter.to_string()),
+            state_line("Energy now", watt_hours(battery.energy_now)),
+            state_line("Energy full", watt_hours(battery.energy_full)),
+            state_line("Energy design", watt_hours(battery.energy_full_design)),
+            state_line(
+                "Health",
+                battery
+                    .health_percent()
+                    .map(|health| format!("{:.1}%", health))
+                    .unwrap_or_else(|| String::from("N/A")),
+            ),
+            state_line(
+                "Cycles",
+                battery
+                    .cycle_count
+                    .map(|count| count.to_string())
+                    .unwrap_or_else(|| String::from("N/A")),
+            ),
+            state_line(
+                "Power draw",
+                battery
+                    .power
+                    .map(|power| format!("{:.2} W", power))
+                    .unwrap_or_else(|| String::from("N/A")),
+            ),
+            state_line("Remaining", remaining),
+        ]);
+
+        frame.render_widget(block, area);
+        frame.render_widget(charge_bar, state_layout[0]);
+        frame.render_widget(Paragraph::new(state_text), state_layout[1]);
+    }
+
+    fn render_discharge_chart<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        history: &MetricsHistory,
+        settings: &DisplaySettings,
+    ) {
+        let discharge_data = window_points(history, &Metric::BatteryDischarge, HISTORY_WINDOW);
+        let max_power = discharge_data
+            .iter()
+            .map(|(_, power)| *power)
+            .fold(1.0, f64::max);
+
+        let datasets = vec![Dataset::default()
+            .name("Discharge rate")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(settings.theme.series[3]))
+            .data(&discharge_data)];
+
+        let discharge_chart = Chart::new(datasets)
+            .block(
+                Block::default()
+                    .title("Discharge rate history")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain),
+            )
+            .x_axis(Axis::default().bounds([0.0, HISTORY_WINDOW.as_secs_f64()]))
+            .y_axis(Axis::default().bounds([0.0, max_power]).labels(vec![
+                Span::raw("0 W"),
+                Span::raw(format!("{:.1} W", max_power / 2.0)),
+                Span::raw(format!("{:.1} W", max_power)),
+            ]));
+
+        frame.render_widget(discharge_chart, area);
+    }
+}
+
+impl<B: Backend> View<B> for BatteryView {
+    /// Renders the state of every battery and the discharge rate history
+    fn render(&mut self, frame: &mut Frame<B>, area: Rect, context: &ViewContext) {
+        let power = &context.snapshot.power;
+        let batteries = &power.supplies.batteries;
+        if batteries.is_empty() {
+            let message = match &power.error {
+                Some(error) => format!("Cannot read {}: {}", power.root.display(), error),
+                None => format!("No battery found in {}", power.root.display()),
+            };
+            let block = Block::default()
+                .title("Battery")
+                .borders(Borders::ALL)
+                .border_type(BorderType::Plain);
+            frame.render_widget(Paragraph::new(Text::from(message)).block(block), area);
+            return;
+        }
+
+        let battery_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(11), Constraint::Min(5)].as_ref())
+            .split(area);
+        let battery_areas = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Ratio(1, batteries.len() as u32);
+                batteries.len()
+            ])
+            .split(battery_layout[0]);
+
+        for (battery, battery_area) in batteries.iter().zip(battery_areas) {
+            self.render_battery_state(
+                frame,
+                battery,
+                power.supplies.ac_online,
+                battery_area,
+                context.settings,
+            );
+        }
+        self.render_discharge_chart(frame, battery_layout[1], context.history, context.settings);
+    }
+}
+
+fn state_line<'a>(label: &'a str, value: String) -> Spans<'a> {
+    Spans::from(vec![
+        Span::styled(
+            format!("{:<15}", label),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(value),
+    ])
+}
@@ -0,0 +1,256 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::{env, io};
+use tui::{backend::Backend, buffer::Cell, layout::Rect, style::Color};
+
+/// Whether the views are drawn with colours
+#[derive(Copy, Clone, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ColorMode {
+    /// Colours unless the terminal is a dumb one or `NO_COLOR` is set
+    #[default]
+    Auto,
+    /// Colours even on a dumb terminal
+    Always,
+    /// No colours, only bold, reversed and the other modifiers
+    Never,
+}
+
+impl ColorMode {
+    pub(crate) fn enabled(self) -> bool {
+        match self {
+            // https://no-color.org
+            ColorMode::Auto => {
+                env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && env::var("TERM").map_or(true, |term| term != "dumb")
+            }
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// How many colours the terminal can draw
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub(crate) enum ColorDepth {
+    /// Guessed from `COLORTERM` and `TERM`
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "16")]
+    Ansi16,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "truecolor")]
+    TrueColor,
+}
+
+impl ColorDepth {
+    fn detect(self) -> ColorDepth {
+        if self != ColorDepth::Auto {
+            return self;
+        }
+        if env::var("COLORTERM").is_ok_and(|value| value == "truecolor" || value == "24bit") {
+            ColorDepth::TrueColor
+        } else if env::var("TERM").is_ok_and(|term| term.contains("256color")) {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    /// Replaces a colour the terminal cannot draw by the closest one it can
+    fn convert(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorDepth::Ansi256, Color::Rgb(red, green, blue)) => {
+                Color::Indexed(closest(16..=255, (red, green, blue)))
+            }
+            (ColorDepth::Ansi16, Color::Rgb(red, green, blue)) => {
+                ANSI_16[closest(0..=15, (red, green, blue)) as usize]
+            }
+            (ColorDepth::Ansi16, Color::Indexed(index)) => {
+                ANSI_16[closest(0..=15, palette_rgb(index)) as usize]
+            }
+            _ => color,
+        }
+    }
+}
+
+/// The first 16 colours of the 256 colour palette
+const ANSI_16: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// Colours of the 256 colour palette, with the xterm defaults for the first 16 ones
+fn palette_rgb(index: u8) -> (u8, u8, u8) {
+    const ANSI_16_RGB: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    match index {
+        0..=15 => ANSI_16_RGB[index as usize],
+        16..=231 => {
+            let cube = index - 16;
+            (
+                CUBE_LEVELS[(cube / 36) as usize],
+                CUBE_LEVELS[(cube / 6 % 6) as usize],
+                CUBE_LEVELS[(cube % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+/// Index of the palette colour closest to `rgb`
+fn closest(indexes: std::ops::RangeInclusive<u8>, rgb: (u8, u8, u8)) -> u8 {
+    let distance = |index: &u8| {
+        let (red, green, blue) = palette_rgb(*index);
+        let delta = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+        delta(red, rgb.0) + delta(green, rgb.1) + delta(blue, rgb.2)
+    };
+    indexes.min_by_key(distance).unwrap_or(0)
+}
+
+/// Wraps a backend and converts the colours of the drawn cells to those the terminal supports,
+/// or drops them when they are disabled. Modifiers like bold and reversed are kept,
+/// so selections stay visible.
+pub(crate) struct ColorBackend<B: Backend> {
+    backend: B,
+    colors: bool,
+    depth: ColorDepth,
+}
+
+impl<B: Backend> ColorBackend<B> {
+    pub(crate) fn new(backend: B, mode: ColorMode, depth: ColorDepth) -> Self {
+        ColorBackend {
+            backend,
+            colors: mode.enabled(),
+            depth: depth.detect(),
+        }
+    }
+
+    /// Applies from the next drawn cells, clear the terminal to redraw everything
+    pub(crate) fn set_mode(&mut self, mode: ColorMode, depth: ColorDepth) {
+        self.colors = mode.enabled();
+        self.depth = depth.detect();
+    }
+}
+
+impl<B: Backend> Backend for ColorBackend<B> {
+    fn draw<'a, I>(&mut self, content: I) -> Result<(), io::Error>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        if self.colors && self.depth == ColorDepth::TrueColor {
+            return self.backend.draw(content);
+        }
+
+        let convert = |color| {
+            if self.colors {
+                self.depth.convert(color)
+            } else {
+                Color::Reset
+            }
+        };
+        let cells: Vec<(u16, u16, Cell)> = content
+            .map(|(x, y, cell)| {
+                let mut cell = cell.clone();
+                cell.fg = convert(cell.fg);
+                cell.bg = convert(cell.bg);
+                (x, y, cell)
+            })
+            .collect();
+        self.backend
+            .draw(cells.iter().map(|(x, y, cell)| (*x, *y, cell)))
+    }
+
+    fn hide_cursor(&mut self) -> Result<(), io::Error> {
+        self.backend.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> Result<(), io::Error> {
+        self.backend.show_cursor()
+    }
+
+    fn get_cursor(&mut self) -> Result<(u16, u16), io::Error> {
+        self.backend.get_cursor()
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> Result<(), io::Error> {
+        self.backend.set_cursor(x, y)
+    }
+
+    fn clear(&mut self) -> Result<(), io::Error> {
+        self.backend.clear()
+    }
+
+    fn size(&self) -> Result<Rect, io::Error> {
+        self.backend.size()
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        self.backend.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ColorDepth;
+    use tui::style::Color;
+
+    #[test]
+    fn converts_colours_to_the_depth_of_the_terminal() {
+        let orange = Color::Rgb(0xff, 0x87, 0x00);
+
+        assert_eq!(ColorDepth::TrueColor.convert(orange), orange);
+        assert_eq!(ColorDepth::Ansi256.convert(orange), Color::Indexed(208));
+        assert_eq!(
+            ColorDepth::Ansi256.convert(Color::Rgb(0x30, 0x30, 0x30)),
+            Color::Indexed(236)
+        );
+        assert_eq!(ColorDepth::Ansi256.convert(Color::Blue), Color::Blue);
+        assert_eq!(
+            ColorDepth::Ansi16.convert(Color::Rgb(250, 10, 10)),
+            Color::LightRed
+        );
+        assert_eq!(ColorDepth::Ansi16.convert(Color::Indexed(28)), Color::Green);
+        assert_eq!(
+            ColorDepth::Ansi16.convert(Color::Indexed(12)),
+            Color::LightBlue
+        );
+    }
+}
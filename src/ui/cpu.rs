@@ -1,39 +1,360 @@
-use std::io::Stdout;
-use sysinfo::{Component, ComponentExt, CpuExt, System, SystemExt};
-use termion::raw::RawTerminal;
+use super::{window_points, DisplaySettings, View, ViewContext};
+use crate::core::{CpuBreakdown, CpuFrequency, CpuSnapshot, CpuStat, Metric, MetricsHistory};
+use crate::keymap::{Action, KeyHint};
+use std::time::Duration;
 use tui::{
-    backend::TermionBackend,
+    backend::Backend,
     layout::{Constraint, Layout, Rect},
-    text::Text,
-    widgets::{BarChart, Block, BorderType, Borders, Paragraph},
+    style::Style,
+    symbols,
+    text::{Span, Spans, Text},
+    widgets::{
+        Axis, Block, BorderType, Borders, Cell, Chart, Dataset, GraphType, Paragraph, Row, Table,
+    },
     Frame,
 };
 
+/// Selectable lengths of the usage history, unless the configuration sets others
+pub const DEFAULT_HISTORY_WINDOWS: [Duration; 3] = [
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(10 * 60),
+];
+/// Maximum number of per-core charts next to each other
+const CORE_CHART_COLUMNS: usize = 4;
+/// CPU states in the stacked time bars, in the order of the `cpu_states` colours of the theme
+const STATES: [&str; 9] = [
+    "user", "nice", "system", "iowait", "irq", "softirq", "steal", "guest", "idle",
+];
+const FREQUENCY_HEADERS: [&str; 7] = [
+    "CPU",
+    "Current",
+    "Min",
+    "Max",
+    "Governor",
+    "EPP",
+    "Throttled",
+];
+/// Width of a legend entry like "■ softirq 100.0%"
+const LEGEND_ENTRY_WIDTH: usize = 18;
+
 pub struct Cpuview {
-    cpu_brand: String,
+    /// Selectable lengths of the usage history, never empty
+    windows: Vec<Duration>,
+    /// Index of the selected window in `windows`
+    window: usize,
+    /// /proc/stat at the last update, the breakdowns are computed from the difference to it
+    cpu_stat: CpuStat,
+    total_breakdown: CpuBreakdown,
+    core_breakdowns: Vec<CpuBreakdown>,
+    frequencies: Vec<CpuFrequency>,
 }
 
 impl Cpuview {
     pub fn new() -> Self {
         Cpuview {
-            cpu_brand: String::new(),
+            windows: DEFAULT_HISTORY_WINDOWS.to_vec(),
+            window: 0,
+            cpu_stat: CpuStat::default(),
+            total_breakdown: CpuBreakdown::default(),
+            core_breakdowns: Vec::new(),
+            frequencies: Vec::new(),
         }
     }
 
-    pub fn render_cpu(
+    /// Renders how the CPU time was spent since the last update as stacked bars,
+    /// for the whole machine and for every core
+    fn render_time_breakdown<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        settings: &DisplaySettings,
+    ) {
+        let block = Block::default()
+            .title("CPU time")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+        let inner_area = block.inner(area);
+        frame.render_widget(block, area);
+
+        let legend_entries: Vec<Span> = STATES
+            .iter()
+            .zip(settings.theme.cpu_states)
+            .zip(state_shares(&self.total_breakdown))
+            .map(|((name, color), share)| {
+                Span::styled(
+                    format!("{} {:<7} {:>5.1}% ", state_symbol(name), name, share),
+                    Style::default().fg(color),
+                )
+            })
+            .collect();
+        let entries_per_line = (inner_area.width as usize / LEGEND_ENTRY_WIDTH).max(1);
+        let legend: Vec<Spans> = legend_entries
+            .chunks(entries_per_line)
+            .map(|entries| Spans::from(entries.to_vec()))
+            .collect();
+        let legend_height = legend.len() as u16;
+        let legend = Paragraph::new(Text::from(legend));
+
+        let breakdown_layout = Layout::default()
+            .direction(tui::layout::Direction::Vertical)
+            .constraints([Constraint::Length(legend_height), Constraint::Min(0)].as_ref())
+            .split(inner_area);
+        frame.render_widget(legend, breakdown_layout[0]);
+
+        // the label takes 6 columns
+        let bar_width = breakdown_layout[1].width.saturating_sub(6) as usize;
+        let mut lines = vec![stacked_bar_line(
+            String::from("all"),
+            &self.total_breakdown,
+            bar_width,
+            settings,
+        )];
+        lines.extend(
+            self.core_breakdowns
+                .iter()
+                .enumerate()
+                .map(|(index, breakdown)| {
+                    stacked_bar_line(format!("cpu{}", index), breakdown, bar_width, settings)
+                }),
+        );
+        frame.render_widget(Paragraph::new(Text::from(lines)), breakdown_layout[1]);
+    }
+
+    /// Renders the frequency scaling state and the thermal throttle counters of every core
+    fn render_frequencies<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        settings: &DisplaySettings,
+    ) {
+        let header_cells = FREQUENCY_HEADERS.iter().map(|header| Cell::from(*header));
+        let table_header = Row::new(header_cells)
+            .style(settings.header_style())
+            .height(1);
+
+        let not_available = || String::from("N/A");
+        let rows = self.frequencies.iter().map(|frequency| {
+            // core and package counters, the package one is shared by the cores of a socket
+            let throttled = match (
+                frequency.core_throttle_count,
+                frequency.package_throttle_count,
+            ) {
+                (None, None) => not_available(),
+                (core, package) => format!(
+                    "{}/{}",
+                    core.map(|count| count.to_string())
+                        .unwrap_or_else(not_available),
+                    package
+                        .map(|count| count.to_string())
+                        .unwrap_or_else(not_available)
+                ),
+            };
+            let cells = [
+                Cell::from(format!("cpu{}", frequency.cpu)),
+                Cell::from(format_frequency(frequency.current)),
+                Cell::from(format_frequency(frequency.min)),
+                Cell::from(format_frequency(frequency.max)),
+                Cell::from(frequency.governor.clone().unwrap_or_else(not_available)),
+                Cell::from(
+                    frequency
+                        .energy_performance_preference
+                        .clone()
+                        .unwrap_or_else(not_available),
+                ),
+                Cell::from(throttled),
+            ];
+            Row::new(cells).height(1)
+        });
+
+        let table = Table::new(rows)
+            .header(table_header)
+            .block(
+                Block::default()
+                    .title("Frequencies")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain),
+            )
+            .widths(&[
+                Constraint::Length(6),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(11),
+                Constraint::Length(11),
+                Constraint::Length(9),
+            ]);
+
+        frame.render_widget(table, area);
+    }
+
+    /// Renders the usage of all cores together over the selected window
+    fn render_total_history<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        context: &ViewContext,
+    ) {
+        let history = context.history;
+        let settings = context.settings;
+        let window = self.windows[self.window];
+        let window_name = format_window(window);
+        let total_data = window_points(history, &Metric::CpuUsage, window);
+        let datasets = vec![Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(settings.theme.series[0]))
+            .data(&total_data)];
+
+        let current_usage = history.latest(&Metric::CpuUsage).unwrap_or(0.0);
+        let window_stats = match history.stats(&Metric::CpuUsage, window) {
+            Some(stats) => format!(
+                "min {:.1}% avg {:.1}% max {:.1}%",
+                stats.min, stats.avg, stats.max
+            ),
+            None => String::from("no samples yet"),
+        };
+        let change_hint = match context
+            .keymap
+            .hint(KeyHint::Action(Action::CycleWindow, "to change"))
+        {
+            Some((key, description)) => format!(" ({} {})", key, description),
+            None => String::new(),
+        };
+        let total_chart = Chart::new(datasets)
+            .block(
+                Block::default()
+                    .title(format!(
+                        "CPU Usage {:.1}% - last {}: {}{}",
+                        current_usage, window_name, window_stats, change_hint
+                    ))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain),
+            )
+            .x_axis(
+                Axis::default()
+                    .bounds([0.0, window.as_secs_f64()])
+                    .labels(vec![
+                        Span::raw(format!("-{}", window_name)),
+                        Span::raw("now"),
+                    ]),
+            )
+            .y_axis(Axis::default().bounds([0.0, 100.0]).labels(vec![
+                Span::raw("0%"),
+                Span::raw("50%"),
+                Span::raw("100%"),
+            ]));
+
+        frame.render_widget(total_chart, area);
+    }
+
+    /// Renders a small usage chart for every core, laid out in a grid
+    fn render_core_histories<B: Backend>(
         &self,
-        frame: &mut Frame<TermionBackend<RawTerminal<Stdout>>>,
+        frame: &mut Frame<B>,
         area: Rect,
-        system: &System,
+        core_count: usize,
+        history: &MetricsHistory,
+        settings: &DisplaySettings,
     ) {
+        if core_count == 0 {
+            return;
+        }
+        let window = self.windows[self.window];
+        let columns = core_count.min(CORE_CHART_COLUMNS);
+        let rows = core_count.div_ceil(columns);
+
+        let row_areas = Layout::default()
+            .direction(tui::layout::Direction::Vertical)
+            .constraints(vec![Constraint::Ratio(1, rows as u32); rows])
+            .split(area);
+        let chart_areas = row_areas.into_iter().flat_map(|row_area| {
+            Layout::default()
+                .direction(tui::layout::Direction::Horizontal)
+                .constraints(vec![Constraint::Ratio(1, columns as u32); columns])
+                .split(row_area)
+        });
+
+        for (core, chart_area) in (0..core_count).zip(chart_areas) {
+            let metric = Metric::CoreUsage(core);
+            let core_data = window_points(history, &metric, window);
+            let datasets = vec![Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(settings.theme.series[1]))
+                .data(&core_data)];
+
+            let current_usage = history.latest(&metric).unwrap_or(0.0);
+            let core_chart = Chart::new(datasets)
+                .block(
+                    Block::default()
+                        .title(format!("Core {} {:.0}%", core, current_usage))
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Plain),
+                )
+                .x_axis(Axis::default().bounds([0.0, window.as_secs_f64()]))
+                .y_axis(Axis::default().bounds([0.0, 100.0]));
+
+            frame.render_widget(core_chart, chart_area);
+        }
+    }
+
+    /// Sets the windows of the configuration, the selected one is kept if it is still listed
+    pub(crate) fn set_windows(&mut self, windows: Vec<Duration>) {
+        let selected = self.windows[self.window];
+        self.window = windows
+            .iter()
+            .position(|&window| window == selected)
+            .unwrap_or(0);
+        self.windows = windows;
+    }
+
+    /// Switches the charts to the next history window
+    pub(crate) fn cycle_window(&mut self) {
+        self.window = (self.window + 1) % self.windows.len();
+    }
+
+    /// Computes the CPU time breakdown since the previous update
+    pub fn update(&mut self, cpu: &CpuSnapshot) {
+        self.total_breakdown = cpu.stat.total.breakdown_since(&self.cpu_stat.total);
+        self.core_breakdowns = cpu
+            .stat
+            .cores
+            .iter()
+            .zip(&self.cpu_stat.cores)
+            .map(|(core, previous)| core.breakdown_since(previous))
+            .collect();
+        self.cpu_stat = cpu.stat.clone();
+        self.frequencies = cpu.frequencies.clone();
+    }
+}
+
+impl<B: Backend> View<B> for Cpuview {
+    fn actions(&self) -> &'static [(Action, &'static str)] {
+        &[(Action::CycleWindow, "history length")]
+    }
+
+    fn render(&mut self, frame: &mut Frame<B>, area: Rect, context: &ViewContext) {
+        let cpu = &context.snapshot.cpu;
+        let history = context.history;
         let cpu_layout = Layout::default()
             .direction(tui::layout::Direction::Horizontal)
-            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)].as_ref())
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)].as_ref())
             .split(area);
 
+        // borders, 3 legend lines, the total and one line per core
+        let time_height = self.core_breakdowns.len() as u16 + 6;
         let cpu_temp_layout = Layout::default()
             .direction(tui::layout::Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .constraints(
+                [
+                    Constraint::Length(5),
+                    Constraint::Length(time_height),
+                    Constraint::Min(6),
+                    Constraint::Length(8),
+                ]
+                .as_ref(),
+            )
             .split(cpu_layout[0]);
 
         let cpu_block = Block::default()
@@ -41,9 +362,9 @@ impl Cpuview {
             .borders(Borders::ALL)
             .border_type(BorderType::Plain);
 
-        let core_count = system.physical_core_count().unwrap_or(1);
-        let cpu_name = system.global_cpu_info().brand();
-        let cpu_freq = system.global_cpu_info().frequency().to_string();
+        let core_count = cpu.physical_core_count.unwrap_or(1);
+        let cpu_name = &cpu.brand;
+        let cpu_freq = cpu.frequency.to_string();
 
         let cpu_text = Text::from(format!(
             "Name: {}\nFreq: {} Mhz\nNumber of cores: {}",
@@ -52,54 +373,228 @@ impl Cpuview {
         let cpu_label = Paragraph::new(cpu_text).block(cpu_block);
 
         frame.render_widget(cpu_label, cpu_temp_layout[0]);
+        self.render_time_breakdown(frame, cpu_temp_layout[1], context.settings);
+        self.render_frequencies(frame, cpu_temp_layout[2], context.settings);
 
         let cpu_temp_block = Block::default()
             .title("Temperatures")
             .borders(Borders::ALL)
             .border_type(BorderType::Plain);
 
-        let sensors: Vec<&Component> = system
-            .components()
-            .iter()
-            .filter(|component| component.label().contains("Core").clone())
-            .collect();
-
         let mut sensor_labels = Text::from("");
-        for s in sensors {
-            let span = Text::raw(format!("{}: {}°C", s.label(), s.temperature()));
+        for (label, temperature) in &cpu.temperatures {
+            let span = Text::raw(format!("{}: {}°C", label, temperature));
             sensor_labels.extend(span);
         }
 
         let sensors = Paragraph::new(sensor_labels).block(cpu_temp_block);
 
-        frame.render_widget(sensors, cpu_temp_layout[1]);
-
-        let mut data = Vec::new();
-        let mut core_titles = Vec::new();
-        let mut i = 0;
-        for _ in system.cpus() {
-            // CPU Core title with index
-            let mut core_title = String::from("Core ");
-            let index_str = i.to_string();
-            core_title.push_str(&index_str);
-            core_titles.push(core_title);
-            i += 1;
+        frame.render_widget(sensors, cpu_temp_layout[3]);
+
+        let history_layout = Layout::default()
+            .direction(tui::layout::Direction::Vertical)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+            .split(cpu_layout[1]);
+
+        self.render_total_history(frame, history_layout[0], context);
+        self.render_core_histories(
+            frame,
+            history_layout[1],
+            cpu.core_usages.len(),
+            history,
+            context.settings,
+        );
+    }
+}
+
+/// Formats a frequency in kHz as MHz or GHz
+/// Names a window like "1 min" or "90 s"
+fn format_window(window: Duration) -> String {
+    let seconds = window.as_secs();
+    if seconds.is_multiple_of(3_600) {
+        format!("{} h", seconds / 3_600)
+    } else if seconds.is_multiple_of(60) {
+        format!("{} min", seconds / 60)
+    } else {
+        format!("{} s", seconds)
+    }
+}
+
+fn format_frequency(khz: Option<u64>) -> String {
+    match khz {
+        Some(khz) if khz >= 1_000_000 => format!("{:.2} GHz", khz as f64 / 1_000_000.0),
+        Some(khz) => format!("{} MHz", khz / 1_000),
+        None => String::from("N/A"),
+    }
+}
+
+/// Returns the shares of the states in the order of STATES
+fn state_shares(breakdown: &CpuBreakdown) -> [f64; 9] {
+    [
+        breakdown.user,
+        breakdown.nice,
+        breakdown.system,
+        breakdown.iowait,
+        breakdown.irq,
+        breakdown.softirq,
+        breakdown.steal,
+        breakdown.guest,
+        breakdown.idle,
+    ]
+}
+
+fn state_symbol(state: &str) -> &'static str {
+    if state == "idle" {
+        "░"
+    } else {
+        "█"
+    }
+}
+
+/// Draws the shares of the states next to each other, `width` columns equal 100%
+fn stacked_bar_line(
+    label: String,
+    breakdown: &CpuBreakdown,
+    width: usize,
+    settings: &DisplaySettings,
+) -> Spans<'static> {
+    let mut spans = vec![Span::raw(format!("{:<6}", label))];
+    let mut share_sum = 0.0;
+    let mut drawn = 0;
+
+    let colors = settings.theme.cpu_states;
+    for ((name, color), share) in STATES.iter().zip(colors).zip(state_shares(breakdown)) {
+        // rounding the running sum keeps the rounding errors from adding up
+        share_sum += share;
+        let end = ((share_sum / 100.0 * width as f64).round() as usize).min(width);
+        if end > drawn {
+            spans.push(Span::styled(
+                state_symbol(name).repeat(end - drawn),
+                Style::default().fg(color),
+            ));
+            drawn = end;
         }
+    }
+    // before the first update every share is 0
+    spans.push(Span::styled(
+        state_symbol("idle").repeat(width - drawn),
+        Style::default().fg(colors[8]),
+    ));
+
+    Spans::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cpuview;
+    use crate::core::{
+        HistoryConfig, MetricsHistory, MetricsSource, ScriptedSource, Snapshot, SnapshotRequest,
+    };
+    use crate::keymap::Keymap;
+    use crate::ui::testing::{cpu_stat, find_line, render_view, render_view_with_keymap, snapshot};
+    use std::time::{Duration, Instant};
+
+    /// Two samples one second apart, half of the time in user space in between
+    fn source() -> ScriptedSource {
+        let mut busy = snapshot();
+        busy.cpu.usage = 70.0;
+        busy.cpu.stat = cpu_stat(50, 25, 25);
+        ScriptedSource::new(vec![snapshot(), busy])
+    }
 
-        i = 0;
-        for cpu in system.cpus() {
-            let usage = cpu.cpu_usage();
-            data.push((core_titles[i].as_str(), usage as u64));
-            i += 1;
+    fn sample(source: &mut ScriptedSource) -> (Cpuview, MetricsHistory, Snapshot) {
+        let mut cpu_view = Cpuview::new();
+        let mut history = MetricsHistory::new(HistoryConfig::default());
+        let started = Instant::now();
+        let mut snapshot = Snapshot::default();
+        for second in 0..2 {
+            snapshot = source.collect(&SnapshotRequest::default());
+            history.record(&snapshot, started + Duration::from_secs(second));
+            cpu_view.update(&snapshot.cpu);
         }
+        (cpu_view, history, snapshot)
+    }
+
+    #[test]
+    fn renders_cpu_information_and_temperatures() {
+        let (mut cpu_view, history, snapshot) = sample(&mut source());
 
-        let cpu_cores_chart = BarChart::default()
-            .block(Block::default().title("CPU Usage").borders(Borders::ALL))
-            .bar_width(8)
-            .data(&data);
+        let lines = render_view(160, 45, &mut cpu_view, &snapshot, &history);
 
-        frame.render_widget(cpu_cores_chart, cpu_layout[1]);
+        find_line(&lines, "Name: Test CPU 3000");
+        find_line(&lines, "Freq: 3000 Mhz");
+        find_line(&lines, "Number of cores: 2");
+        find_line(&lines, "Core 0: 42°C");
+        find_line(&lines, "Core 1: 44°C");
     }
 
-    pub fn update(&mut self) {}
+    #[test]
+    fn renders_time_breakdown_between_snapshots() {
+        let (mut cpu_view, history, snapshot) = sample(&mut source());
+
+        let lines = render_view(160, 45, &mut cpu_view, &snapshot, &history);
+
+        let legend = find_line(&lines, "user     50.0%");
+        assert!(lines[legend].contains("system   25.0%"));
+        find_line(&lines, "idle     25.0%");
+    }
+
+    #[test]
+    fn renders_usage_statistics_of_the_window() {
+        let (mut cpu_view, history, snapshot) = sample(&mut source());
+
+        let lines = render_view(160, 45, &mut cpu_view, &snapshot, &history);
+
+        find_line(
+            &lines,
+            "CPU Usage 70.0% - last 1 min: min 50.0% avg 60.0% max 70.0%",
+        );
+        find_line(&lines, "Core 1 60%");
+    }
+
+    #[test]
+    fn names_the_key_bound_to_change_the_window() {
+        let (mut cpu_view, history, snapshot) = sample(&mut source());
+
+        let lines = render_view(160, 45, &mut cpu_view, &snapshot, &history);
+        find_line(&lines, "max 70.0% (w to change)");
+
+        let keymap = Keymap::new(&toml::from_str("cycle_window = \"F5\"").unwrap()).unwrap();
+        let lines = render_view_with_keymap(160, 45, &mut cpu_view, &snapshot, &history, &keymap);
+        find_line(&lines, "max 70.0% (F5 to change)");
+    }
+
+    #[test]
+    fn cycles_through_the_configured_windows() {
+        let (mut cpu_view, history, snapshot) = sample(&mut source());
+        let title = |cpu_view: &mut Cpuview| {
+            let lines = render_view(160, 45, cpu_view, &snapshot, &history);
+            lines[find_line(&lines, "CPU Usage")].clone()
+        };
+
+        cpu_view.set_windows(vec![Duration::from_secs(30), Duration::from_secs(60 * 60)]);
+        assert!(title(&mut cpu_view).contains("last 30 s:"));
+        cpu_view.cycle_window();
+        assert!(title(&mut cpu_view).contains("last 1 h:"));
+
+        // the selected window is kept while the configuration still lists it
+        cpu_view.set_windows(vec![Duration::from_secs(90), Duration::from_secs(60 * 60)]);
+        assert!(title(&mut cpu_view).contains("last 1 h:"));
+        cpu_view.cycle_window();
+        assert!(title(&mut cpu_view).contains("last 90 s:"));
+        cpu_view.set_windows(vec![Duration::from_secs(5 * 60)]);
+        assert!(title(&mut cpu_view).contains("last 5 min:"));
+    }
+
+    #[test]
+    fn renders_frequencies() {
+        let (mut cpu_view, history, snapshot) = sample(&mut source());
+
+        let lines = render_view(160, 45, &mut cpu_view, &snapshot, &history);
+
+        let first_cpu = find_line(&lines, "3.00 GHz");
+        assert!(lines[first_cpu].contains("powersave"));
+        assert!(lines[first_cpu].contains("performance"));
+        find_line(&lines, "800 MHz");
+    }
 }
@@ -0,0 +1,180 @@
+use super::{window_points, DisplaySettings, View, ViewContext, HISTORY_WINDOW};
+use crate::core::{MemInfo, MemorySnapshot, Metric, MetricsHistory};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    symbols,
+    text::{Span, Spans, Text},
+    widgets::{Axis, Block, BorderType, Borders, Chart, Dataset, Gauge, GraphType, Paragraph},
+    Frame,
+};
+
+pub struct MemoryView {
+    meminfo: MemInfo,
+}
+
+impl MemoryView {
+    pub fn new() -> Self {
+        MemoryView {
+            meminfo: MemInfo::default(),
+        }
+    }
+
+    fn render_ram<B: Backend>(&self, frame: &mut Frame<B>, area: Rect, settings: &DisplaySettings) {
+        let block = Block::default()
+            .title("RAM")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+        let ram_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1)].as_ref())
+            .margin(1)
+            .split(area);
+
+        let meminfo = &self.meminfo;
+        let used_percent = meminfo.used_percent() as u16;
+        let ram_usage_bar = Gauge::default()
+            .percent(used_percent.min(100))
+            .label(format!(
+                "{} / {}",
+                settings.format_kib(meminfo.used()),
+                settings.format_kib(meminfo.total)
+            ))
+            .gauge_style(Style::default().fg(settings.color_for_percent(used_percent)));
+
+        let ram_text = Text::from(vec![
+            memory_line(settings, "Total", meminfo.total),
+            memory_line(settings, "Used", meminfo.used()),
+            memory_line(settings, "Free", meminfo.free),
+            memory_line(settings, "Available", meminfo.available),
+            memory_line(settings, "Cached", meminfo.cached),
+            memory_line(settings, "Buffers", meminfo.buffers),
+            memory_line(settings, "Shared", meminfo.shared),
+            memory_line(settings, "Slab", meminfo.slab),
+        ]);
+
+        frame.render_widget(block, area);
+        frame.render_widget(ram_usage_bar, ram_layout[0]);
+        frame.render_widget(Paragraph::new(ram_text), ram_layout[1]);
+    }
+
+    fn render_swap<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        settings: &DisplaySettings,
+    ) {
+        let block = Block::default()
+            .title("Swap")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+        let swap_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1)].as_ref())
+            .margin(1)
+            .split(area);
+
+        let meminfo = &self.meminfo;
+        let used_percent = meminfo.swap_used_percent() as u16;
+        let swap_usage_bar = Gauge::default()
+            .percent(used_percent.min(100))
+            .label(format!(
+                "{} / {}",
+                settings.format_kib(meminfo.swap_used()),
+                settings.format_kib(meminfo.swap_total)
+            ))
+            .gauge_style(Style::default().fg(settings.color_for_percent(used_percent)));
+
+        let swap_text = Text::from(vec![
+            memory_line(settings, "Total", meminfo.swap_total),
+            memory_line(settings, "Used", meminfo.swap_used()),
+            memory_line(settings, "Free", meminfo.swap_free),
+            memory_line(settings, "Cached", meminfo.swap_cached),
+            Spans::from(""),
+            memory_line(settings, "Dirty", meminfo.dirty),
+            memory_line(settings, "Writeback", meminfo.writeback),
+        ]);
+
+        frame.render_widget(block, area);
+        frame.render_widget(swap_usage_bar, swap_layout[0]);
+        frame.render_widget(Paragraph::new(swap_text), swap_layout[1]);
+    }
+
+    fn render_history<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        history: &MetricsHistory,
+        settings: &DisplaySettings,
+    ) {
+        let ram_data = window_points(history, &Metric::MemoryUsed, HISTORY_WINDOW);
+        let swap_data = window_points(history, &Metric::SwapUsed, HISTORY_WINDOW);
+        let title = match history.stats(&Metric::MemoryUsed, HISTORY_WINDOW) {
+            Some(stats) => format!(
+                "Usage history - RAM min {:.1}% avg {:.1}% max {:.1}%",
+                stats.min, stats.avg, stats.max
+            ),
+            None => String::from("Usage history"),
+        };
+
+        let datasets = vec![
+            Dataset::default()
+                .name("RAM")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(settings.theme.series[0]))
+                .data(&ram_data),
+            Dataset::default()
+                .name("Swap")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(settings.theme.series[2]))
+                .data(&swap_data),
+        ];
+
+        let history_chart = Chart::new(datasets)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain),
+            )
+            .x_axis(Axis::default().bounds([0.0, HISTORY_WINDOW.as_secs_f64()]))
+            .y_axis(Axis::default().bounds([0.0, 100.0]).labels(vec![
+                Span::raw("0%"),
+                Span::raw("50%"),
+                Span::raw("100%"),
+            ]));
+
+        frame.render_widget(history_chart, area);
+    }
+
+    /// Takes the memory statistics of a snapshot
+    pub fn update(&mut self, memory: &MemorySnapshot) {
+        self.meminfo = memory.meminfo;
+    }
+}
+
+impl<B: Backend> View<B> for MemoryView {
+    /// Renders RAM and swap usage with a detailed breakdown and a history chart
+    fn render(&mut self, frame: &mut Frame<B>, area: Rect, context: &ViewContext) {
+        let history = context.history;
+        let memory_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(11), Constraint::Min(5)].as_ref())
+            .split(area);
+        let usage_layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(memory_layout[0]);
+
+        self.render_ram(frame, usage_layout[0], context.settings);
+        self.render_swap(frame, usage_layout[1], context.settings);
+        self.render_history(frame, memory_layout[1], history, context.settings);
+    }
+}
+
+fn memory_line(settings: &DisplaySettings, label: &str, kib: u64) -> Spans<'static> {
+    Spans::from(format!("{:<10} {:>12}", label, settings.format_kib(kib)))
+}
@@ -1,31 +1,213 @@
-use std::io::Stdout;
-
-use termion::raw::RawTerminal;
-use tui::backend::TermionBackend;
-use tui::layout::Rect;
-use tui::style::Color;
+use crate::core::{Metric, MetricsHistory, Snapshot};
+use crate::keymap::{Action, KeyHint, Keymap};
+use byte_unit::{Byte, ByteUnit};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::time::Duration;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Style};
 use tui::Frame;
 
+mod battery;
+mod color;
 mod cpu;
+mod memory;
+mod network;
 mod overview;
+mod pressure;
+mod process_details;
 mod processes;
+mod sensors;
+mod storage;
+#[cfg(test)]
+pub(crate) mod testing;
+mod theme;
 
-pub use self::cpu::Cpuview;
+pub use self::battery::BatteryView;
+pub(crate) use self::color::{ColorBackend, ColorDepth, ColorMode};
+pub use self::cpu::{Cpuview, DEFAULT_HISTORY_WINDOWS};
+pub use self::memory::MemoryView;
+pub use self::network::NetworkView;
 pub use self::overview::Overview;
+pub use self::pressure::PressureView;
 pub use self::processes::ProcessesView;
+pub(crate) use self::processes::{TableSort, TableSortPredicate};
+pub use self::sensors::SensorsView;
+pub use self::storage::StorageView;
+pub use self::theme::Theme;
+
+/// Which multiples the amounts of bytes are displayed with
+#[derive(Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    /// KiB, MiB, GiB, ...
+    #[default]
+    Binary,
+    /// KB, MB, GB, ...
+    Decimal,
+}
+
+/// Percentages from which the gauges turn yellow, light red and red
+#[derive(Copy, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    pub warning: u16,
+    pub high: u16,
+    pub critical: u16,
+}
+
+impl Thresholds {
+    pub const DEFAULT: Thresholds = Thresholds {
+        warning: 51,
+        high: 75,
+        critical: 91,
+    };
+}
 
-/// Returns a color for Gauge widget's bar based on a percentage
-pub fn color_for_percent(percentage: u16) -> Color {
-    match percentage {
-        0..=50 => Color::LightGreen,
-        51..=74 => Color::Yellow,
-        75..=90 => Color::LightRed,
-        91..=100 => Color::Red,
-        _ => Color::White,
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds::DEFAULT
     }
 }
 
+/// Units, thresholds and colours of the configuration.
+/// Every view gets them through its `ViewContext` and formats with them.
 #[derive(Copy, Clone)]
+pub struct DisplaySettings {
+    pub units: Units,
+    pub thresholds: Thresholds,
+    pub theme: Theme,
+}
+
+impl DisplaySettings {
+    pub const DEFAULT: DisplaySettings = DisplaySettings {
+        units: Units::Binary,
+        thresholds: Thresholds::DEFAULT,
+        theme: Theme::DARK,
+    };
+
+    /// Style of the header rows of the tables
+    pub fn header_style(&self) -> Style {
+        Style::default()
+            .bg(self.theme.header)
+            .fg(self.theme.header_text)
+    }
+
+    /// Returns a color for Gauge widget's bar based on a percentage
+    pub fn color_for_percent(&self, percentage: u16) -> Color {
+        let DisplaySettings {
+            thresholds, theme, ..
+        } = self;
+        match percentage {
+            101.. => theme.text,
+            _ if percentage >= thresholds.critical => theme.critical,
+            _ if percentage >= thresholds.high => theme.high,
+            _ if percentage >= thresholds.warning => theme.warning,
+            _ => theme.normal,
+        }
+    }
+
+    /// Formats an amount of bytes with the most appropriate unit
+    pub fn format_bytes(&self, bytes: u64) -> String {
+        Byte::from_bytes(bytes)
+            .get_appropriate_unit(self.units == Units::Binary)
+            .to_string()
+    }
+
+    /// Formats an amount of kibibytes with the most appropriate unit
+    pub fn format_kib(&self, kib: u64) -> String {
+        Byte::from_unit(kib as f64, ByteUnit::KiB)
+            .map(|bytes| {
+                bytes
+                    .get_appropriate_unit(self.units == Units::Binary)
+                    .to_string()
+            })
+            .unwrap_or_else(|_| String::from("N/A"))
+    }
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        DisplaySettings::DEFAULT
+    }
+}
+
+/// Length of the history charts which do not have a selectable window
+pub const HISTORY_WINDOW: Duration = Duration::from_secs(60);
+
+/// Maps the samples of a metric within `window` to chart points in seconds,
+/// aligning the last recorded snapshot to the right edge at `window`
+pub fn window_points(
+    history: &MetricsHistory,
+    metric: &Metric,
+    window: Duration,
+) -> Vec<(f64, f64)> {
+    let now = match history.last_record() {
+        Some(now) => now,
+        None => return Vec::new(),
+    };
+    history
+        .window(metric, window)
+        .iter()
+        .map(|sample| {
+            let age = now.saturating_duration_since(sample.time);
+            ((window - age).as_secs_f64(), sample.value)
+        })
+        .collect()
+}
+
+/// Formats seconds as e.g. "2d 3h 4m 5s"
+pub fn format_duration(seconds: u64) -> String {
+    let (days, hours, minutes, seconds) = (
+        seconds / 86_400,
+        seconds % 86_400 / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60,
+    );
+
+    if days > 0 {
+        format!("{}d {}h {}m {}s", days, hours, minutes, seconds)
+    } else if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Returns an area for a popup centered in `area`,
+/// `percent_x` of its width and `height` lines high
+pub fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let vertical_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(area.height.saturating_sub(height) / 2),
+                Constraint::Length(height),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical_layout[1])[1]
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[allow(clippy::upper_case_acronyms)]
 pub(crate) enum Tab {
     Overview,
     CPU,
@@ -33,50 +215,65 @@ pub(crate) enum Tab {
     Processes,
     Storage,
     Network,
-}
-
-impl From<Tab> for usize {
-    fn from(tab: Tab) -> Self {
-        match tab {
-            Tab::Overview => 0,
-            Tab::CPU => 1,
-            Tab::Memory => 2,
-            Tab::Processes => 3,
-            Tab::Storage => 4,
-            Tab::Network => 5,
-        }
-    }
+    Sensors,
+    Battery,
+    Pressure,
 }
 
 impl Tab {
-    pub(crate) fn next(&mut self) {
-        match self {
-            Tab::Overview => *self = Tab::CPU,
-            Tab::CPU => *self = Tab::Memory,
-            Tab::Memory => *self = Tab::Processes,
-            Tab::Processes => *self = Tab::Storage,
-            Tab::Storage => *self = Tab::Network,
-            Tab::Network => *self = Tab::Overview,
-        }
-    }
+    /// Every tab in the default order
+    pub(crate) const ALL: [Tab; 9] = [
+        Tab::Overview,
+        Tab::CPU,
+        Tab::Memory,
+        Tab::Processes,
+        Tab::Storage,
+        Tab::Network,
+        Tab::Sensors,
+        Tab::Battery,
+        Tab::Pressure,
+    ];
 
-    pub(crate) fn previous(&mut self) {
+    pub(crate) fn title(self) -> &'static str {
         match self {
-            Tab::Overview => *self = Tab::Network,
-            Tab::CPU => *self = Tab::Overview,
-            Tab::Memory => *self = Tab::CPU,
-            Tab::Processes => *self = Tab::Memory,
-            Tab::Storage => *self = Tab::Processes,
-            Tab::Network => *self = Tab::Storage,
+            Tab::Overview => "Overview",
+            Tab::CPU => "CPU",
+            Tab::Memory => "Memory",
+            Tab::Processes => "Processes",
+            Tab::Storage => "Storage",
+            Tab::Network => "Network",
+            Tab::Sensors => "Sensors",
+            Tab::Battery => "Battery",
+            Tab::Pressure => "Pressure",
         }
     }
 }
 
-pub trait View {
-    /// Renders the visual representation of a system's resources.
-    fn render(&self, frame: &mut Frame<TermionBackend<RawTerminal<Stdout>>>, area: Rect);
+/// What the views render besides their own state
+pub struct ViewContext<'a> {
+    pub snapshot: &'a Snapshot,
+    pub history: &'a MetricsHistory,
+    /// The keys are named after their bindings
+    pub(crate) keymap: &'a Keymap,
+    pub settings: &'a DisplaySettings,
+}
+
+/// A tab of the application. Views only draw through `Frame`, so they render
+/// to the terminal as well as to `TestBackend` or any other tui backend.
+pub trait View<B: Backend> {
+    /// Renders the visual representation of a system's resources into `area`.
+    fn render(&mut self, frame: &mut Frame<B>, area: Rect, context: &ViewContext);
 
-    /// Updates the system information of the system's resources to render them in the next frame.
-    /// This method should only update the system's information related only to a specific resources.
-    fn update(&mut self);
+    /// The actions the view handles and what they do, listed by the help
+    fn actions(&self) -> &'static [(Action, &'static str)] {
+        &[]
+    }
+
+    /// The most useful keys in the current state of the view, shown in the footer
+    fn key_hints(&self) -> Vec<KeyHint> {
+        self.actions()
+            .iter()
+            .map(|(action, description)| KeyHint::Action(*action, description))
+            .collect()
+    }
 }
@@ -0,0 +1,273 @@
+use super::{window_points, DisplaySettings, View, ViewContext, HISTORY_WINDOW};
+use crate::core::{Key, Metric, MetricsHistory, NetworkSnapshot};
+use crate::keymap::Action;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    symbols,
+    text::Span,
+    widgets::{
+        Axis, Block, BorderType, Borders, Cell, Chart, Dataset, GraphType, Row, Table, TableState,
+    },
+    Frame,
+};
+
+const CELL_HEADERS: [&str; 11] = [
+    "Interface",
+    "State",
+    "RX/s",
+    "TX/s",
+    "RX total",
+    "TX total",
+    "RX packets",
+    "TX packets",
+    "RX errors",
+    "TX errors",
+    "Drops RX/TX",
+];
+
+/// Counters of a network interface at the last update.
+struct Interface {
+    name: String,
+    state: String,
+    rx_per_sec: f64,
+    tx_per_sec: f64,
+    rx_total: u64,
+    tx_total: u64,
+    rx_packets: u64,
+    tx_packets: u64,
+    rx_errors: u64,
+    tx_errors: u64,
+    rx_drops: u64,
+    tx_drops: u64,
+}
+
+pub struct NetworkView {
+    interfaces: Vec<Interface>,
+    table_state: TableState,
+}
+
+impl NetworkView {
+    pub fn new() -> Self {
+        NetworkView {
+            interfaces: Vec::new(),
+            table_state: TableState::default(),
+        }
+    }
+
+    fn render_interfaces<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        settings: &DisplaySettings,
+    ) {
+        let header_cells = CELL_HEADERS.iter().map(|header| Cell::from(*header));
+        let table_header = Row::new(header_cells)
+            .style(settings.header_style())
+            .height(1);
+
+        let rows = self.interfaces.iter().map(|interface| {
+            let cells = [
+                Cell::from(interface.name.clone()),
+                Cell::from(interface.state.clone()),
+                Cell::from(format!(
+                    "{}/s",
+                    settings.format_bytes(interface.rx_per_sec as u64)
+                )),
+                Cell::from(format!(
+                    "{}/s",
+                    settings.format_bytes(interface.tx_per_sec as u64)
+                )),
+                Cell::from(settings.format_bytes(interface.rx_total)),
+                Cell::from(settings.format_bytes(interface.tx_total)),
+                Cell::from(interface.rx_packets.to_string()),
+                Cell::from(interface.tx_packets.to_string()),
+                Cell::from(interface.rx_errors.to_string()),
+                Cell::from(interface.tx_errors.to_string()),
+                Cell::from(format!("{}/{}", interface.rx_drops, interface.tx_drops)),
+            ];
+            Row::new(cells).height(1)
+        });
+
+        let table = Table::new(rows)
+            .header(table_header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain)
+                    .title("Interfaces"),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(">> ")
+            .widths(&[
+                Constraint::Length(12),
+                Constraint::Length(8),
+                Constraint::Length(13),
+                Constraint::Length(13),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(11),
+                Constraint::Length(11),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(12),
+            ]);
+
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+
+    fn render_throughput_chart<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        history: &MetricsHistory,
+        settings: &DisplaySettings,
+    ) {
+        let selected = self
+            .table_state
+            .selected()
+            .and_then(|index| self.interfaces.get(index));
+        let title = match selected {
+            Some(interface) => format!("Throughput of {}", interface.name),
+            None => String::from("Throughput"),
+        };
+
+        let (rx_data, tx_data) = match selected {
+            Some(interface) => (
+                window_points(
+                    history,
+                    &Metric::NetworkReceived(interface.name.clone()),
+                    HISTORY_WINDOW,
+                ),
+                window_points(
+                    history,
+                    &Metric::NetworkTransmitted(interface.name.clone()),
+                    HISTORY_WINDOW,
+                ),
+            ),
+            None => (Vec::new(), Vec::new()),
+        };
+        let max_rate = rx_data
+            .iter()
+            .chain(tx_data.iter())
+            .map(|(_, rate)| *rate)
+            .fold(1024.0, f64::max);
+
+        let datasets = vec![
+            Dataset::default()
+                .name("RX")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(settings.theme.series[0]))
+                .data(&rx_data),
+            Dataset::default()
+                .name("TX")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(settings.theme.series[1]))
+                .data(&tx_data),
+        ];
+
+        let throughput_chart = Chart::new(datasets)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain),
+            )
+            .x_axis(Axis::default().bounds([0.0, HISTORY_WINDOW.as_secs_f64()]))
+            .y_axis(Axis::default().bounds([0.0, max_rate]).labels(vec![
+                Span::raw("0 B/s"),
+                Span::raw(format!(
+                    "{}/s",
+                    settings.format_bytes((max_rate / 2.0) as u64)
+                )),
+                Span::raw(format!("{}/s", settings.format_bytes(max_rate as u64))),
+            ]));
+
+        frame.render_widget(throughput_chart, area);
+    }
+
+    /// Moves the interface selection which the throughput chart is drawn for
+    pub(crate) fn handle_arrow_keys(&mut self, key: Key) {
+        if self.interfaces.is_empty() {
+            return;
+        }
+        let last = self.interfaces.len() - 1;
+        let selected = self.table_state.selected().unwrap_or(0);
+
+        match key {
+            Key::Up => self.table_state.select(Some(selected.saturating_sub(1))),
+            Key::Down => self.table_state.select(Some((selected + 1).min(last))),
+            _ => (),
+        }
+    }
+
+    /// Takes the counters of every interface, the throughput is the latest recorded one
+    pub fn update(&mut self, networks: &[NetworkSnapshot], history: &MetricsHistory) {
+        let per_sec = |metric: Metric| history.latest(&metric).unwrap_or(0.0);
+
+        // the snapshot lists the interfaces ordered by name
+        let interfaces: Vec<Interface> = networks
+            .iter()
+            .map(|network| Interface {
+                name: network.name.clone(),
+                state: network.state.clone().unwrap_or_else(|| String::from("N/A")),
+                rx_per_sec: per_sec(Metric::NetworkReceived(network.name.clone())),
+                tx_per_sec: per_sec(Metric::NetworkTransmitted(network.name.clone())),
+                rx_total: network.total_received,
+                tx_total: network.total_transmitted,
+                rx_packets: network.total_packets_received,
+                tx_packets: network.total_packets_transmitted,
+                rx_errors: network.total_errors_on_received,
+                tx_errors: network.total_errors_on_transmitted,
+                rx_drops: network.rx_drops,
+                tx_drops: network.tx_drops,
+            })
+            .collect();
+
+        // keep the selection on the same interface when interfaces appear or disappear
+        let selected_name = self
+            .table_state
+            .selected()
+            .and_then(|index| self.interfaces.get(index))
+            .map(|interface| interface.name.clone());
+        let selected = match selected_name {
+            Some(name) => interfaces
+                .iter()
+                .position(|interface| interface.name == name),
+            None => None,
+        };
+        self.table_state.select(match selected {
+            Some(index) => Some(index),
+            None if interfaces.is_empty() => None,
+            None => Some(0),
+        });
+
+        self.interfaces = interfaces;
+    }
+}
+
+impl<B: Backend> View<B> for NetworkView {
+    fn actions(&self) -> &'static [(Action, &'static str)] {
+        &[
+            (Action::Up, "previous interface"),
+            (Action::Down, "next interface"),
+        ]
+    }
+
+    /// Renders the counters of every network interface and the throughput chart of the selected one
+    fn render(&mut self, frame: &mut Frame<B>, area: Rect, context: &ViewContext) {
+        let history = context.history;
+        // borders and the header take 2 + 1 lines
+        let interfaces_height = self.interfaces.len() as u16 + 3;
+        let network_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(interfaces_height), Constraint::Min(5)].as_ref())
+            .split(area);
+
+        self.render_interfaces(frame, network_layout[0], context.settings);
+        self.render_throughput_chart(frame, network_layout[1], history, context.settings);
+    }
+}
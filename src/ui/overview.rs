@@ -1,31 +1,33 @@
-use super::color_for_percent;
+use super::{DisplaySettings, View, ViewContext};
+use crate::core::{CpuBreakdown, CpuStat, Pressure, ResourcePressure, Snapshot};
 use byte_unit::{Byte, ByteUnit};
-use std::io::Stdout;
-use sysinfo::{CpuExt, DiskExt, System, SystemExt};
-use termion::raw::RawTerminal;
 use tui::{
-    backend::TermionBackend,
+    backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
     style::Style,
     text::{Span, Spans, Text},
-    widgets::{BarChart, Block, BorderType, Borders, Gauge, Paragraph},
+    widgets::{Block, BorderType, Borders, Gauge, Paragraph},
     Frame,
 };
 
 #[derive(Default)]
 pub struct Overview {
-    system_info: System,
     os: String,
     os_version: String,
     kernel_version: String,
     host_name: String,
     uptime: u64,
+    load_average: [f64; 3],
+    cpu_name: String,
+    cpu_frequency: u64,
+    cpu_cores: usize,
     cpu_load: usize,
+    cpu_stat: CpuStat,
+    cpu_breakdown: CpuBreakdown,
+    pressure: ResourcePressure,
     memory_total: u64,
     memory_used: u64,
-    disk_space_total: u64,
-    disk_space_used: u64,
-    process_count: u64,
+    memory_available: u64,
     network_status: String,
     network_sent: u64,
     network_received: u64,
@@ -33,55 +35,10 @@ pub struct Overview {
 
 impl Overview {
     pub fn new() -> Self {
-        // TODO: refresh only memory and cpu
-        let system_info = System::new_all();
-        let os = system_info.name().unwrap_or("N/A".to_string());
-        let os_version = system_info.os_version().unwrap_or("N/A".to_string());
-        let kernel_version = system_info.kernel_version().unwrap_or("N/A".to_string());
-        let host_name = system_info.host_name().unwrap_or("N/A".to_string());
-
-        Overview {
-            os,
-            os_version,
-            kernel_version,
-            host_name,
-            ..Default::default()
-        }
+        Overview::default()
     }
 
-    /// Renders the system resources overview: cpu, memory, disks, network infos
-    pub fn render_overview(
-        &mut self,
-        frame: &mut Frame<TermionBackend<RawTerminal<Stdout>>>,
-        area: Rect,
-    ) {
-        self.system_info.refresh_cpu();
-
-        // Layout
-        let overview_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25),
-                ]
-                .as_ref(),
-            )
-            .split(area);
-
-        self.render_system_info(frame, &overview_layout);
-        self.render_cpu(frame, &overview_layout);
-        //self.render_memory(frame, &overview_layout);
-        //self.render_disks(frame, overview_layout[2]);
-    }
-
-    fn render_system_info(
-        &self,
-        frame: &mut Frame<TermionBackend<RawTerminal<Stdout>>>,
-        layout: &Vec<Rect>,
-    ) {
+    fn render_system_info<B: Backend>(&self, frame: &mut Frame<B>, layout: &[Rect]) {
         let system_info_layout = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
@@ -89,16 +46,11 @@ impl Overview {
             .split(layout[0]);
 
         // Data
-        let overview = overview(&self.system_info);
-        let uptime = overview.uptime.to_string();
-        let load_data = self.system_info.load_average();
-        let average_load = format!(
-            "1m: {}% 5m: {}% 15m: {}%",
-            load_data.one, load_data.five, load_data.fifteen
-        );
+        let uptime = self.uptime.to_string();
+        let [one, five, fifteen] = self.load_average;
+        let average_load = format!("1m: {}% 5m: {}% 15m: {}%", one, five, fifteen);
 
         // Widgets
-        //let system_info_area = Rect::new(area.x, area.y, area.width, area.height);
         let block = Block::default()
             .borders(Borders::ALL)
             .title("System information");
@@ -106,12 +58,15 @@ impl Overview {
         let spans = vec![
             Spans::from(vec![
                 Span::raw("Operating system: "),
-                Span::raw(overview.os),
+                Span::raw(self.os.as_str()),
+            ]),
+            Spans::from(vec![
+                Span::raw("Version: "),
+                Span::raw(self.os_version.as_str()),
             ]),
-            Spans::from(vec![Span::raw("Version: "), Span::raw(overview.os_version)]),
             Spans::from(vec![
                 Span::raw("Kernel version: "),
-                Span::raw(overview.kernel_version),
+                Span::raw(self.kernel_version.as_str()),
             ]),
         ];
         let os_text = Text::from(spans);
@@ -119,8 +74,20 @@ impl Overview {
 
         let spans2 = vec![
             Spans::from(vec![Span::raw("Uptime: "), Span::raw(uptime)]),
-            Spans::from(vec![Span::raw("Hostname: "), Span::raw(overview.host_name)]),
+            Spans::from(vec![
+                Span::raw("Hostname: "),
+                Span::raw(self.host_name.as_str()),
+            ]),
             Spans::from(vec![Span::raw("Avg Load: "), Span::raw(average_load)]),
+            Spans::from(vec![
+                Span::raw("Pressure (some avg10): "),
+                Span::raw(format!(
+                    "CPU {} Memory {} I/O {}",
+                    format_some_avg10(self.pressure.cpu),
+                    format_some_avg10(self.pressure.memory),
+                    format_some_avg10(self.pressure.io)
+                )),
+            ]),
         ];
         let uptime_text = Text::from(spans2);
         let uptime_label = Paragraph::new(uptime_text);
@@ -134,10 +101,11 @@ impl Overview {
     }
 
     /// Renders CPU basic information with an usage bar
-    fn render_cpu(
+    fn render_cpu<B: Backend>(
         &mut self,
-        frame: &mut Frame<TermionBackend<RawTerminal<Stdout>>>,
-        layout: &Vec<Rect>,
+        frame: &mut Frame<B>,
+        layout: &[Rect],
+        settings: &DisplaySettings,
     ) {
         let cpu_memory_layout = Layout::default()
             .direction(Direction::Horizontal)
@@ -149,12 +117,9 @@ impl Overview {
             .margin(1)
             .split(cpu_memory_layout[0]);
 
-        self.render_memory(frame, cpu_memory_layout[1]);
+        self.render_memory(frame, cpu_memory_layout[1], settings);
 
-        let cpu_name = self.system_info.global_cpu_info().brand();
-        let cpu_freq = self.system_info.global_cpu_info().frequency().to_string();
-        let cpu_cores = self.system_info.physical_core_count().unwrap_or(0);
-        let cpu_usage = self.system_info.global_cpu_info().cpu_usage() as u16;
+        let cpu_usage = self.cpu_load.min(100) as u16;
 
         let cpu_block = Block::default()
             .title("CPU")
@@ -163,12 +128,17 @@ impl Overview {
         frame.render_widget(cpu_block, cpu_memory_layout[0]);
 
         let cpu_text = Text::from(format!(
-            "Name: {}\nFreq: {} Mhz\nCores: {}\nUsage: {}%",
-            cpu_name, cpu_freq, cpu_cores, cpu_usage
+            "Name: {}\nFreq: {} Mhz\nCores: {}\nUsage: {}%\nIOwait: {:.1}% Steal: {:.1}%",
+            self.cpu_name,
+            self.cpu_frequency,
+            self.cpu_cores,
+            cpu_usage,
+            self.cpu_breakdown.iowait,
+            self.cpu_breakdown.steal
         ));
         let cpu_label = Paragraph::new(cpu_text);
 
-        let gauge_bar_color = color_for_percent(cpu_usage);
+        let gauge_bar_color = settings.color_for_percent(cpu_usage);
         let cpu_usage_bar = Gauge::default()
             .percent(cpu_usage)
             .gauge_style(Style::default().fg(gauge_bar_color));
@@ -177,12 +147,12 @@ impl Overview {
     }
 
     /// Renders memory statistics with an usage bar
-    fn render_memory(
+    fn render_memory<B: Backend>(
         &mut self,
-        frame: &mut Frame<TermionBackend<RawTerminal<Stdout>>>,
+        frame: &mut Frame<B>,
         area: Rect,
+        settings: &DisplaySettings,
     ) {
-        self.system_info.refresh_memory();
         let memory_layout = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
@@ -195,12 +165,13 @@ impl Overview {
             .border_type(BorderType::Plain);
 
         let (total_memory, used_memory, available_memory) = (
-            Byte::from_unit(self.system_info.total_memory() as f64, ByteUnit::KB).unwrap(),
-            Byte::from_unit(self.system_info.used_memory() as f64, ByteUnit::KB).unwrap(),
-            Byte::from_unit(self.system_info.available_memory() as f64, ByteUnit::KB).unwrap(),
+            Byte::from_unit(self.memory_total as f64, ByteUnit::KB).unwrap(),
+            Byte::from_unit(self.memory_used as f64, ByteUnit::KB).unwrap(),
+            Byte::from_unit(self.memory_available as f64, ByteUnit::KB).unwrap(),
         );
 
-        let one_percent = total_memory.get_bytes() / 100;
+        // the first snapshot may not have arrived yet
+        let one_percent = (total_memory.get_bytes() / 100).max(1);
         let used_percent = used_memory.get_bytes() / one_percent;
 
         let memory_label = Paragraph::new(Text::from(format!(
@@ -215,143 +186,183 @@ impl Overview {
 
         let memory_usage_bar = Gauge::default()
             .percent(used_percent as u16)
-            .gauge_style(Style::default().fg(color_for_percent(used_percent as u16)));
+            .gauge_style(Style::default().fg(settings.color_for_percent(used_percent as u16)));
         frame.render_widget(memory_usage_bar, memory_layout[1]);
     }
 
-    fn render_disks(&self, frame: &mut Frame<TermionBackend<RawTerminal<Stdout>>>, area: Rect) {
-        let disks = self.system_info.disks();
-        let mut total_space = 0;
-        let mut available_space = 0;
-
-        for disk in disks {
-            available_space += disk.available_space();
-            total_space += disk.total_space();
-        }
-
-        let total_space_gb = Byte::from_bytes(total_space).get_adjusted_unit(ByteUnit::GB);
-        let used_space_gb =
-            Byte::from_bytes(total_space - available_space).get_adjusted_unit(ByteUnit::GB);
-        let available_space_gb = Byte::from_bytes(available_space).get_adjusted_unit(ByteUnit::GB);
-
-        let disks_text = Text::from(format!(
-            "Total space: {}\nUsed space: {}\nAvailable space: {}",
-            total_space_gb, used_space_gb, available_space_gb
+    /// Renders the connection status with the total amount of sent and received data
+    fn render_network<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        settings: &DisplaySettings,
+    ) {
+        let network_text = Text::from(format!(
+            "Status: {}\nSent: {}\nReceived: {}",
+            self.network_status,
+            settings.format_bytes(self.network_sent),
+            settings.format_bytes(self.network_received)
         ));
-        let disks_label = Paragraph::new(disks_text).block(
+        let network_label = Paragraph::new(network_text).block(
             Block::default()
-                .title("Disks")
+                .title("Network")
                 .borders(Borders::ALL)
                 .border_type(BorderType::Plain),
         );
 
-        let one_percent = total_space / 100;
-        let used = total_space - available_space;
-        let usage_percent = used / one_percent;
+        frame.render_widget(network_label, area);
+    }
+}
 
-        let disk_layout = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+impl<B: Backend> View<B> for Overview {
+    /// Renders the system resources overview: cpu, memory, disks, network infos
+    fn render(&mut self, frame: &mut Frame<B>, area: Rect, context: &ViewContext) {
+        // Layout
+        let overview_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Percentage(25),
+                    Constraint::Percentage(25),
+                    Constraint::Percentage(25),
+                    Constraint::Percentage(25),
+                ]
+                .as_ref(),
+            )
             .split(area);
-        let gauge_bar_color = color_for_percent(usage_percent as u16);
-        let disk_usage_bar = Gauge::default()
-            .percent(usage_percent as u16)
-            .gauge_style(Style::default().fg(gauge_bar_color))
-            .block(
-                Block::default()
-                    .title("Storage space usage")
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Plain),
-            );
-
-        frame.render_widget(disks_label, disk_layout[0]);
-        frame.render_widget(disk_usage_bar, disk_layout[1]);
-    }
 
-    pub fn tick(&mut self) {
-        //TODO: refresh only dynamic stats: freq, usage
-        self.system_info.refresh_cpu();
-        self.system_info.refresh_memory();
+        self.render_system_info(frame, &overview_layout);
+        self.render_cpu(frame, &overview_layout, context.settings);
+        self.render_network(frame, overview_layout[2], context.settings);
     }
 }
 
 impl Overview {
-    pub fn os(mut self, os_string: String) -> Self {
-        self.os = os_string;
-        self
-    }
-
-    pub fn os_version(mut self, os_version: String) -> Self {
-        self.os_version = os_version;
-        self
-    }
-
-    pub fn kernel_version(mut self, kernel_version: String) -> Self {
-        self.kernel_version = kernel_version;
-        self
-    }
-
-    pub fn host_name(mut self, host_name: String) -> Self {
-        self.host_name = host_name;
-        self
-    }
-
-    pub fn uptime(mut self, uptime: u64) -> Self {
-        self.uptime = uptime;
-        self
-    }
-
-    pub fn cpu_load(mut self, cpu_load: usize) -> Self {
-        self.cpu_load = cpu_load;
-        self
-    }
-
-    pub fn memory_total(mut self, memory_total: u64) -> Self {
-        self.memory_total = memory_total;
-        self
+    pub fn update(&mut self, snapshot: &Snapshot) {
+        let host = &snapshot.host;
+        self.os = host.os.clone();
+        self.os_version = host.os_version.clone();
+        self.kernel_version = host.kernel_version.clone();
+        self.host_name = host.host_name.clone();
+        self.uptime = host.uptime;
+        self.load_average = host.load_average;
+
+        let cpu = &snapshot.cpu;
+        self.cpu_name = cpu.brand.clone();
+        self.cpu_frequency = cpu.frequency;
+        self.cpu_cores = cpu.physical_core_count.unwrap_or(0);
+        self.cpu_load = cpu.usage as usize;
+        self.cpu_breakdown = cpu.stat.total.breakdown_since(&self.cpu_stat.total);
+        self.cpu_stat = cpu.stat.clone();
+
+        self.memory_total = snapshot.memory.total;
+        self.memory_used = snapshot.memory.used;
+        self.memory_available = snapshot.memory.available;
+        self.pressure = snapshot.pressure.clone();
+
+        // the loopback interface is always up and would hide a missing connection
+        let interfaces: Vec<_> = snapshot
+            .networks
+            .iter()
+            .filter(|network| network.name != "lo")
+            .collect();
+        let connected = interfaces
+            .iter()
+            .any(|network| network.state.as_deref() == Some("up"));
+
+        self.network_status = if connected {
+            String::from("Connected")
+        } else {
+            String::from("Disconnected")
+        };
+        self.network_sent = interfaces
+            .iter()
+            .map(|network| network.total_transmitted)
+            .sum();
+        self.network_received = interfaces
+            .iter()
+            .map(|network| network.total_received)
+            .sum();
     }
+}
 
-    pub fn memory_used(mut self, memory_used: u64) -> Self {
-        self.memory_used = memory_used;
-        self
+fn format_some_avg10(pressure: Option<Pressure>) -> String {
+    match pressure {
+        Some(pressure) => format!("{:.2}%", pressure.some.avg10),
+        None => String::from("N/A"),
     }
+}
 
-    pub fn disk_space_total(mut self, disk_space_total: u64) -> Self {
-        self.disk_space_total = disk_space_total;
-        self
+#[cfg(test)]
+mod tests {
+    use super::Overview;
+    use crate::core::{HistoryConfig, MetricsHistory, Snapshot};
+    use crate::ui::testing::{find_line, render_view, render_view_with_settings, snapshot};
+    use crate::ui::{DisplaySettings, Units};
+
+    #[test]
+    fn renders_host_information() {
+        let mut overview = Overview::new();
+        let snapshot = snapshot();
+        overview.update(&snapshot);
+
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let lines = render_view(120, 40, &mut overview, &snapshot, &history);
+
+        find_line(&lines, "Operating system: Heimdal Linux");
+        find_line(&lines, "Kernel version: 6.1.0-test");
+        find_line(&lines, "Hostname: testhost");
+        find_line(&lines, "Uptime: 7200");
+        find_line(&lines, "1m: 1.5% 5m: 1% 15m: 0.5%");
     }
 
-    pub fn disk_space_used(mut self, disk_space_used: u64) -> Self {
-        self.disk_space_used = disk_space_used;
-        self
+    #[test]
+    fn renders_cpu_memory_and_network() {
+        let mut overview = Overview::new();
+        let snapshot = snapshot();
+        overview.update(&snapshot);
+
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let lines = render_view(120, 40, &mut overview, &snapshot, &history);
+
+        find_line(&lines, "Name: Test CPU 3000");
+        find_line(&lines, "Freq: 3000 Mhz");
+        find_line(&lines, "Cores: 2");
+        find_line(&lines, "Usage: 50%");
+        find_line(&lines, "Total memory: 8.00 GB");
+        find_line(&lines, "Used Memory: 2.00 GB");
+        find_line(&lines, "Status: Connected");
+        find_line(&lines, "Received: 2.00 KiB");
     }
 
-    pub fn process_count(mut self, process_count: u64) -> Self {
-        self.process_count = process_count;
-        self
+    #[test]
+    fn formats_the_amounts_with_the_units_of_its_context() {
+        let mut overview = Overview::new();
+        let snapshot = snapshot();
+        overview.update(&snapshot);
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let decimal = DisplaySettings {
+            units: Units::Decimal,
+            ..DisplaySettings::DEFAULT
+        };
+
+        let lines =
+            render_view_with_settings(120, 40, &mut overview, &snapshot, &history, &decimal);
+        find_line(&lines, "Received: 2.05 KB");
+
+        // the settings of one render do not stick to the next one
+        let lines = render_view(120, 40, &mut overview, &snapshot, &history);
+        find_line(&lines, "Received: 2.00 KiB");
     }
 
-    pub fn network_status(mut self, network_status: String) -> Self {
-        self.network_status = network_status;
-        self
-    }
+    #[test]
+    fn renders_before_the_first_snapshot() {
+        let mut overview = Overview::new();
 
-    pub fn network_sent(mut self, network_sent: u64) -> Self {
-        self.network_sent = network_sent;
-        self
-    }
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let lines = render_view(120, 40, &mut overview, &Snapshot::default(), &history);
 
-    pub fn network_received(mut self, network_received: u64) -> Self {
-        self.network_received = network_received;
-        self
+        find_line(&lines, "Uptime: 0");
+        find_line(&lines, "Usage: 0%");
     }
-
-    pub fn update(&mut self) {
-        self.system_info.refresh_cpu();
-    }
-}
-
-fn overview(system_info: &System) -> Overview {
-    const N_A: &'static str = "N/A";
-    Overview::new().uptime(system_info.uptime())
 }
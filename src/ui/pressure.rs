@@ -0,0 +1,453 @@
+use super::{DisplaySettings, View, ViewContext, HISTORY_WINDOW};
+use crate::core::{
+    CgroupPressure, Key, Metric, MetricsHistory, PressureAverages, Resource, ResourcePressure,
+};
+use crate::keymap::Action;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Sparkline, Table, TableState},
+    Frame,
+};
+
+const CGROUP_HEADERS: [&str; 6] = [
+    "CPU some",
+    "CPU full",
+    "Memory some",
+    "Memory full",
+    "I/O some",
+    "I/O full",
+];
+
+const RESOURCES: [(Resource, &str); 3] = [
+    (Resource::Cpu, "CPU"),
+    (Resource::Memory, "Memory"),
+    (Resource::Io, "I/O"),
+];
+
+pub struct PressureView {
+    /// Names of the control groups in the order of the last rendering
+    cgroup_names: Vec<String>,
+    table_state: TableState,
+    /// The selection follows this control group as the order changes
+    selected_cgroup: Option<String>,
+}
+
+impl PressureView {
+    pub fn new() -> Self {
+        PressureView {
+            cgroup_names: Vec::new(),
+            table_state: TableState::default(),
+            selected_cgroup: None,
+        }
+    }
+
+    /// The control group whose history is drawn
+    pub(crate) fn selected_cgroup(&self) -> Option<String> {
+        self.selected_cgroup.clone()
+    }
+
+    /// Draws the control groups ordered by their highest avg10 value.
+    /// They are None until the first snapshot read since the view became visible.
+    fn render_cgroups<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        cgroups: Option<&Vec<CgroupPressure>>,
+        settings: &DisplaySettings,
+    ) {
+        let mut sorted: Vec<&CgroupPressure> = cgroups.into_iter().flatten().collect();
+        sorted.sort_by(|a, b| highest_avg10(&b.pressure).total_cmp(&highest_avg10(&a.pressure)));
+        self.cgroup_names = sorted.iter().map(|cgroup| cgroup.name.clone()).collect();
+        // the selection is kept until the control groups are read
+        if cgroups.is_some() {
+            let selected = self
+                .selected_cgroup
+                .as_ref()
+                .and_then(|name| self.cgroup_names.iter().position(|other| other == name));
+            let selected = match (selected, self.table_state.selected()) {
+                _ if sorted.is_empty() => None,
+                (Some(selected), _) => Some(selected),
+                (None, Some(selected)) => Some(selected.min(sorted.len() - 1)),
+                (None, None) => Some(0),
+            };
+            self.select(selected);
+        }
+
+        // the second line of the header names the averaging windows of the columns
+        let windows = format!("{:>6}{:>6}{:>6}", "10s", "60s", "300s");
+        let header_cells = std::iter::once(Cell::from("Control group")).chain(
+            CGROUP_HEADERS
+                .iter()
+                .map(|header| Cell::from(Text::from(format!("{}\n{}", header, windows)))),
+        );
+        let table_header = Row::new(header_cells)
+            .style(settings.header_style())
+            .height(2);
+
+        let rows = sorted.iter().map(|cgroup| {
+            let pressure = &cgroup.pressure;
+            let cells = [
+                Cell::from(cgroup.name.clone()),
+                Cell::from(format_averages(pressure.cpu.map(|cpu| cpu.some))),
+                Cell::from(format_averages(pressure.cpu.and_then(|cpu| cpu.full))),
+                Cell::from(format_averages(pressure.memory.map(|memory| memory.some))),
+                Cell::from(format_averages(
+                    pressure.memory.and_then(|memory| memory.full),
+                )),
+                Cell::from(format_averages(pressure.io.map(|io| io.some))),
+                Cell::from(format_averages(pressure.io.and_then(|io| io.full))),
+            ];
+            Row::new(cells).height(1)
+        });
+
+        let title = if cgroups.map(Vec::is_empty).unwrap_or(false) {
+            "Control groups (%) - no cgroup v2 pressure files found"
+        } else {
+            "Control groups (%)"
+        };
+        let table = Table::new(rows)
+            .header(table_header)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(">> ")
+            .widths(&[
+                Constraint::Min(20),
+                Constraint::Length(18),
+                Constraint::Length(18),
+                Constraint::Length(18),
+                Constraint::Length(18),
+                Constraint::Length(18),
+                Constraint::Length(18),
+            ]);
+
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+
+    /// Draws the avg10 history of the selected control group
+    fn render_cgroup_history<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        history: &MetricsHistory,
+        settings: &DisplaySettings,
+    ) {
+        let name = match &self.selected_cgroup {
+            Some(name) => name,
+            None => {
+                let block = Block::default()
+                    .title("History")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain);
+                frame.render_widget(block, area);
+                return;
+            }
+        };
+        let block = Block::default()
+            .title(format!("History of {} (avg10)", name))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+        let resource_areas = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Ratio(1, 3),
+                    Constraint::Ratio(1, 3),
+                    Constraint::Ratio(1, 3),
+                ]
+                .as_ref(),
+            )
+            .split(block.inner(area));
+        frame.render_widget(block, area);
+
+        let theme = &settings.theme;
+        for ((resource, title), resource_area) in RESOURCES.into_iter().zip(resource_areas) {
+            let resource_layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Length(1),
+                        Constraint::Length(2),
+                        Constraint::Min(1),
+                    ]
+                    .as_ref(),
+                )
+                .horizontal_margin(1)
+                .split(resource_area);
+            let label = Spans::from(vec![
+                Span::styled(title, Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(" some / full", Style::default().fg(theme.dim)),
+            ]);
+            frame.render_widget(Paragraph::new(label), resource_layout[0]);
+
+            let some = Metric::CgroupPressureSome(name.clone(), resource);
+            render_sparkline(frame, resource_layout[1], history, &some, theme.series[3]);
+            let full = Metric::CgroupPressureFull(name.clone(), resource);
+            render_sparkline(frame, resource_layout[2], history, &full, theme.series[2]);
+        }
+    }
+
+    fn select(&mut self, index: Option<usize>) {
+        self.table_state.select(index);
+        self.selected_cgroup = index.and_then(|index| self.cgroup_names.get(index).cloned());
+    }
+
+    /// Moves the control group selection
+    pub(crate) fn handle_arrow_keys(&mut self, key: Key) {
+        if self.cgroup_names.is_empty() {
+            return;
+        }
+        let last = self.cgroup_names.len() - 1;
+        let selected = self.table_state.selected().unwrap_or(0);
+
+        match key {
+            Key::Up => self.select(Some(selected.saturating_sub(1))),
+            Key::Down => self.select(Some((selected + 1).min(last))),
+            Key::Home => self.select(Some(0)),
+            Key::End => self.select(Some(last)),
+            _ => (),
+        }
+    }
+}
+
+impl<B: Backend> View<B> for PressureView {
+    fn actions(&self) -> &'static [(Action, &'static str)] {
+        &[
+            (Action::Up, "previous control group"),
+            (Action::Down, "next control group"),
+            (Action::Home, "first control group"),
+            (Action::End, "last control group"),
+        ]
+    }
+
+    /// Renders the system-wide pressure of every resource with its history
+    /// and the pressure of the control groups with the history of the selected one
+    fn render(&mut self, frame: &mut Frame<B>, area: Rect, context: &ViewContext) {
+        let pressure_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(10),
+                    Constraint::Min(5),
+                    Constraint::Length(6),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let resource_layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Ratio(1, 3),
+                    Constraint::Ratio(1, 3),
+                    Constraint::Ratio(1, 3),
+                ]
+                .as_ref(),
+            )
+            .split(pressure_layout[0]);
+
+        for ((resource, title), resource_area) in RESOURCES.into_iter().zip(resource_layout) {
+            render_resource(frame, resource_area, title, resource, context);
+        }
+        self.render_cgroups(
+            frame,
+            pressure_layout[1],
+            context.snapshot.cgroup_pressure.as_ref(),
+            context.settings,
+        );
+        self.render_cgroup_history(frame, pressure_layout[2], context.history, context.settings);
+    }
+}
+
+fn render_resource<B: Backend>(
+    frame: &mut Frame<B>,
+    area: Rect,
+    title: &str,
+    resource: Resource,
+    context: &ViewContext,
+) {
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain);
+    let resource_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Length(2),
+                Constraint::Length(1),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
+        .margin(1)
+        .split(area);
+    frame.render_widget(block, area);
+
+    let pressure = match context.snapshot.pressure.get(resource) {
+        Some(pressure) => pressure,
+        None => {
+            frame.render_widget(
+                Paragraph::new("Not available, is PSI enabled in the kernel?"),
+                resource_layout[0],
+            );
+            return;
+        }
+    };
+
+    let averages_line = |kind: &str, averages: Option<_>| match averages {
+        Some(averages) => averages_spans(kind, averages),
+        None => Spans::from(format!("{:<6}N/A", kind)),
+    };
+    let averages_text = Text::from(vec![
+        Spans::from(Span::styled(
+            format!("{:<6}{:>8}{:>8}{:>8}", "", "avg10", "avg60", "avg300"),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        averages_line("some", Some(pressure.some)),
+        averages_line("full", pressure.full),
+    ]);
+    frame.render_widget(Paragraph::new(averages_text), resource_layout[0]);
+
+    let theme = &context.settings.theme;
+    let history = context.history;
+    let some = Metric::PressureSome(resource);
+    render_sparkline(frame, resource_layout[1], history, &some, theme.series[3]);
+    frame.render_widget(
+        Paragraph::new(Span::styled("some / full", Style::default().fg(theme.dim))),
+        resource_layout[2],
+    );
+    let full = Metric::PressureFull(resource);
+    render_sparkline(frame, resource_layout[3], history, &full, theme.series[2]);
+}
+
+/// Draws a history of percentages, scaled to at least 1% so that noise stays flat
+fn render_sparkline<B: Backend>(
+    frame: &mut Frame<B>,
+    area: Rect,
+    history: &MetricsHistory,
+    metric: &Metric,
+    color: Color,
+) {
+    // the sparkline takes integers, hundredths of a percent keep the precision of the kernel
+    let data: Vec<u64> = history
+        .window(metric, HISTORY_WINDOW)
+        .iter()
+        .map(|sample| (sample.value * 100.0) as u64)
+        .collect();
+    let max = data.iter().copied().max().unwrap_or(0).max(100);
+    // show the newest samples when the area is narrower than the history
+    let skipped = data.len().saturating_sub(area.width as usize);
+
+    let sparkline = Sparkline::default()
+        .data(&data[skipped..])
+        .max(max)
+        .style(Style::default().fg(color));
+    frame.render_widget(sparkline, area);
+}
+
+fn averages_spans<'a>(kind: &str, averages: PressureAverages) -> Spans<'a> {
+    Spans::from(format!(
+        "{:<6}{:>7.2}%{:>7.2}%{:>7.2}%",
+        kind, averages.avg10, averages.avg60, averages.avg300
+    ))
+}
+
+fn format_averages(averages: Option<PressureAverages>) -> String {
+    match averages {
+        Some(averages) => format!(
+            "{:>6.2}{:>6.2}{:>6.2}",
+            averages.avg10, averages.avg60, averages.avg300
+        ),
+        None => format!("{:>6}", "N/A"),
+    }
+}
+
+fn highest_avg10(pressure: &ResourcePressure) -> f64 {
+    [pressure.cpu, pressure.memory, pressure.io]
+        .iter()
+        .flatten()
+        .flat_map(|pressure| [Some(pressure.some), pressure.full])
+        .flatten()
+        .map(|averages| averages.avg10)
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PressureView;
+    use crate::core::{
+        CgroupPressure, HistoryConfig, Key, MetricsHistory, Pressure, PressureAverages,
+        ResourcePressure, Snapshot,
+    };
+    use crate::ui::testing::{find_line, render_view};
+    use std::time::{Duration, Instant};
+
+    fn cgroup(name: &str, avg10: f64) -> CgroupPressure {
+        let averages = PressureAverages {
+            avg10,
+            avg60: avg10 / 2.0,
+            avg300: avg10 / 4.0,
+            total: 0,
+        };
+        CgroupPressure {
+            name: name.to_string(),
+            pressure: ResourcePressure {
+                cpu: Some(Pressure {
+                    some: averages,
+                    full: None,
+                }),
+                ..ResourcePressure::default()
+            },
+        }
+    }
+
+    #[test]
+    fn shows_the_averages_and_history_of_the_selected_control_group() {
+        let start = Instant::now();
+        let mut history = MetricsHistory::new(HistoryConfig::default());
+        let mut pressure_view = PressureView::new();
+        let snapshot = Snapshot {
+            cgroup_pressure: Some(vec![
+                cgroup("/system.slice", 4.0),
+                cgroup("/user.slice", 2.0),
+            ]),
+            ..Snapshot::default()
+        };
+        render_view(160, 40, &mut pressure_view, &snapshot, &history);
+        pressure_view.handle_arrow_keys(Key::Down);
+        history.follow_cgroup(pressure_view.selected_cgroup());
+        history.record(&snapshot, start);
+
+        // the selection stays on the control group when it moves up
+        let reordered = Snapshot {
+            cgroup_pressure: Some(vec![
+                cgroup("/system.slice", 1.0),
+                cgroup("/user.slice", 8.0),
+            ]),
+            ..Snapshot::default()
+        };
+        history.record(&reordered, start + Duration::from_secs(1));
+        let lines = render_view(160, 40, &mut pressure_view, &reordered, &history);
+
+        find_line(&lines, "10s   60s  300s");
+        let user = find_line(&lines, ">> /user.slice");
+        assert!(lines[user].contains("  8.00  4.00  2.00    N/A"));
+        assert!(user < find_line(&lines, "/system.slice"));
+        // both samples of the CPU, 2% then 8%, at the left of the sparkline
+        let history_title = find_line(&lines, "History of /user.slice (avg10)");
+        assert!(lines[history_title + 3].starts_with("│ ▄█ "));
+        assert_eq!(
+            pressure_view.selected_cgroup().as_deref(),
+            Some("/user.slice")
+        );
+    }
+}
@@ -0,0 +1,394 @@
+use super::{format_duration, DisplaySettings, HISTORY_WINDOW};
+use crate::core::{
+    Key, MemoryMapping, Metric, MetricsHistory, ProcessDetailsSnapshot, ProcessSnapshot,
+    SeriesStats, Snapshot,
+};
+use sysinfo::Pid;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, BorderType, Borders, Paragraph, Tabs},
+    Frame,
+};
+
+const SECTION_TITLES: [&str; 5] = [
+    "General",
+    "Environment",
+    "Limits",
+    "Open files",
+    "Memory maps",
+];
+
+#[derive(Copy, Clone)]
+enum DetailsSection {
+    General,
+    Environment,
+    Limits,
+    OpenFiles,
+    MemoryMaps,
+}
+
+impl From<DetailsSection> for usize {
+    fn from(section: DetailsSection) -> Self {
+        match section {
+            DetailsSection::General => 0,
+            DetailsSection::Environment => 1,
+            DetailsSection::Limits => 2,
+            DetailsSection::OpenFiles => 3,
+            DetailsSection::MemoryMaps => 4,
+        }
+    }
+}
+
+impl DetailsSection {
+    fn next(&mut self) {
+        match self {
+            DetailsSection::General => *self = DetailsSection::Environment,
+            DetailsSection::Environment => *self = DetailsSection::Limits,
+            DetailsSection::Limits => *self = DetailsSection::OpenFiles,
+            DetailsSection::OpenFiles => *self = DetailsSection::MemoryMaps,
+            DetailsSection::MemoryMaps => *self = DetailsSection::General,
+        }
+    }
+
+    fn previous(&mut self) {
+        match self {
+            DetailsSection::General => *self = DetailsSection::MemoryMaps,
+            DetailsSection::Environment => *self = DetailsSection::General,
+            DetailsSection::Limits => *self = DetailsSection::Environment,
+            DetailsSection::OpenFiles => *self = DetailsSection::Limits,
+            DetailsSection::MemoryMaps => *self = DetailsSection::OpenFiles,
+        }
+    }
+}
+
+/// Everything known about a process in the latest snapshot
+struct ProcessDetails<'a> {
+    process: &'a ProcessSnapshot,
+    parent: Option<(Pid, String)>,
+    /// Usage over the history window
+    cpu_stats: Option<SeriesStats>,
+    memory_stats: Option<SeriesStats>,
+    files: &'a ProcessDetailsSnapshot,
+}
+
+impl<'a> ProcessDetails<'a> {
+    fn load(
+        process: &'a ProcessSnapshot,
+        snapshot: &'a Snapshot,
+        history: &MetricsHistory,
+    ) -> Option<Self> {
+        // the files are read from the snapshot after the panel was opened
+        let files = snapshot
+            .process_details
+            .as_ref()
+            .filter(|files| files.pid == process.pid)?;
+        let parent = process.parent.map(|parent_pid| {
+            let parent_name = snapshot
+                .process(parent_pid)
+                .map(|parent| parent.name.clone())
+                .unwrap_or_else(|| String::from("N/A"));
+            (parent_pid, parent_name)
+        });
+
+        Some(ProcessDetails {
+            process,
+            parent,
+            cpu_stats: history.stats(&Metric::ProcessCpu(process.pid), HISTORY_WINDOW),
+            memory_stats: history.stats(&Metric::ProcessMemory(process.pid), HISTORY_WINDOW),
+            files,
+        })
+    }
+}
+
+/// Detail panel of a single process, opened from the Processes view
+pub(crate) struct ProcessDetailsView {
+    pid: Pid,
+    section: DetailsSection,
+    scroll: u16,
+}
+
+impl ProcessDetailsView {
+    pub(crate) fn new(pid: Pid) -> Self {
+        ProcessDetailsView {
+            pid,
+            section: DetailsSection::General,
+            scroll: 0,
+        }
+    }
+
+    /// The process whose details are shown
+    pub(crate) fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    /// Switches between the sections with left/right and scrolls the section with the other keys
+    pub(crate) fn handle_key(&mut self, key: Key) {
+        match key {
+            Key::Left => {
+                self.section.previous();
+                self.scroll = 0;
+            }
+            Key::Right | Key::Char('\t') => {
+                self.section.next();
+                self.scroll = 0;
+            }
+            Key::Up => self.scroll = self.scroll.saturating_sub(1),
+            Key::Down => self.scroll = self.scroll.saturating_add(1),
+            Key::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            Key::PageDown => self.scroll = self.scroll.saturating_add(10),
+            Key::Home => self.scroll = 0,
+            Key::End => self.scroll = u16::MAX,
+            _ => (),
+        }
+    }
+
+    pub(crate) fn render<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        snapshot: &Snapshot,
+        history: &MetricsHistory,
+        settings: &DisplaySettings,
+    ) {
+        let process = snapshot.process(self.pid);
+        let title = match process {
+            Some(process) => format!("Process {} ({})", self.pid, process.name),
+            None => format!("Process {}", self.pid),
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+        let inner_area = block.inner(area);
+        frame.render_widget(block, area);
+
+        let details_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(1)].as_ref())
+            .split(inner_area);
+
+        let sections = SECTION_TITLES
+            .iter()
+            .map(|title| Spans::from(*title))
+            .collect();
+        let section_tabs = Tabs::new(sections)
+            .select(self.section.into())
+            .highlight_style(Style::default().fg(settings.theme.highlight))
+            .divider(Span::raw("|"))
+            .block(Block::default().borders(Borders::BOTTOM));
+        frame.render_widget(section_tabs, details_layout[0]);
+
+        let lines = match process {
+            Some(process) => match ProcessDetails::load(process, snapshot, history) {
+                Some(details) => self.section_lines(&details, settings),
+                None => vec![Spans::from("Loading…")],
+            },
+            None => vec![Spans::from("The process is not running anymore.")],
+        };
+        // do not scroll past the last line
+        let max_scroll = (lines.len() as u16).saturating_sub(details_layout[1].height);
+        self.scroll = self.scroll.min(max_scroll);
+
+        let content = Paragraph::new(Text::from(lines)).scroll((self.scroll, 0));
+        frame.render_widget(content, details_layout[1]);
+    }
+
+    fn section_lines(
+        &self,
+        details: &ProcessDetails,
+        settings: &DisplaySettings,
+    ) -> Vec<Spans<'static>> {
+        match self.section {
+            DetailsSection::General => general_lines(details, settings),
+            DetailsSection::Environment => {
+                if details.files.environment.is_empty() {
+                    vec![Spans::from(
+                        "No environment variables or they are not readable.",
+                    )]
+                } else {
+                    details
+                        .files
+                        .environment
+                        .iter()
+                        .map(|variable| Spans::from(variable.clone()))
+                        .collect()
+                }
+            }
+            DetailsSection::Limits => match &details.files.limits {
+                Ok(limits) => {
+                    let mut lines = vec![header_line(format!(
+                        "{:<26}{:>21}{:>21}  {}",
+                        "Limit", "Soft", "Hard", "Units"
+                    ))];
+                    lines.extend(limits.iter().map(|limit| {
+                        Spans::from(format!(
+                            "{:<26}{:>21}{:>21}  {}",
+                            limit.name, limit.soft, limit.hard, limit.units
+                        ))
+                    }));
+                    lines
+                }
+                Err(error) => vec![error_line(error, settings)],
+            },
+            DetailsSection::OpenFiles => match &details.files.open_files {
+                Ok(open_files) => {
+                    let mut lines = vec![header_line(format!("{:>6}  {}", "FD", "Target"))];
+                    lines.extend(open_files.iter().map(|open_file| {
+                        Spans::from(format!("{:>6}  {}", open_file.fd, open_file.target))
+                    }));
+                    lines
+                }
+                Err(error) => vec![error_line(error, settings)],
+            },
+            DetailsSection::MemoryMaps => match &details.files.memory_maps {
+                Ok(memory_maps) => memory_map_lines(memory_maps, settings),
+                Err(error) => vec![error_line(error, settings)],
+            },
+        }
+    }
+}
+
+fn general_lines(details: &ProcessDetails, settings: &DisplaySettings) -> Vec<Spans<'static>> {
+    let parent = match &details.parent {
+        Some((pid, name)) => format!("{} ({})", pid, name),
+        None => String::from("N/A"),
+    };
+    let process = details.process;
+    let threads = match &details.files.threads {
+        Ok(threads) => threads.to_string(),
+        Err(error) => error.clone(),
+    };
+    let cpu_usage = match details.cpu_stats {
+        Some(stats) => format!(
+            "min {:.1}%  avg {:.1}%  max {:.1}%",
+            stats.min, stats.avg, stats.max
+        ),
+        None => String::from("N/A"),
+    };
+    let memory_usage = match details.memory_stats {
+        Some(stats) => format!(
+            "min {}  avg {}  max {}",
+            settings.format_kib(stats.min as u64),
+            settings.format_kib(stats.avg as u64),
+            settings.format_kib(stats.max as u64)
+        ),
+        None => String::from("N/A"),
+    };
+
+    vec![
+        field_line("Command line", process.command.join(" ")),
+        field_line("Executable", process.executable.display().to_string()),
+        field_line(
+            "Working directory",
+            process.working_directory.display().to_string(),
+        ),
+        field_line("Parent", parent),
+        field_line("Status", process.status.clone()),
+        field_line("Threads", threads),
+        field_line("Started", format_timestamp(process.start_time)),
+        field_line("Running for", format_duration(process.run_time)),
+        field_line("Resident memory", settings.format_kib(process.memory)),
+        field_line(
+            "Virtual memory",
+            settings.format_kib(process.virtual_memory),
+        ),
+        field_line("CPU last minute", cpu_usage),
+        field_line("RSS last minute", memory_usage),
+    ]
+}
+
+fn memory_map_lines(
+    memory_maps: &[MemoryMapping],
+    settings: &DisplaySettings,
+) -> Vec<Spans<'static>> {
+    let (rss, pss, swap) = memory_maps
+        .iter()
+        .fold((0, 0, 0), |(rss, pss, swap), mapping| {
+            (rss + mapping.rss, pss + mapping.pss, swap + mapping.swap)
+        });
+
+    let mut lines = vec![
+        Spans::from(format!(
+            "Total RSS: {}  PSS: {}  Swap: {}  in {} mappings",
+            settings.format_kib(rss),
+            settings.format_kib(pss),
+            settings.format_kib(swap),
+            memory_maps.len()
+        )),
+        header_line(format!(
+            "{:<34}{:<6}{:>12}{:>12}{:>12}  {}",
+            "Address", "Perm", "RSS", "PSS", "Swap", "Path"
+        )),
+    ];
+    lines.extend(memory_maps.iter().map(|mapping| {
+        Spans::from(format!(
+            "{:<34}{:<6}{:>12}{:>12}{:>12}  {}",
+            mapping.address,
+            mapping.permissions,
+            settings.format_kib(mapping.rss),
+            settings.format_kib(mapping.pss),
+            settings.format_kib(mapping.swap),
+            mapping.path
+        ))
+    }));
+    lines
+}
+
+fn field_line(label: &str, value: String) -> Spans<'static> {
+    Spans::from(vec![
+        Span::styled(
+            format!("{:<19}", label),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(value),
+    ])
+}
+
+fn header_line(text: String) -> Spans<'static> {
+    Spans::from(Span::styled(
+        text,
+        Style::default().add_modifier(Modifier::BOLD),
+    ))
+}
+
+fn error_line(error: &str, settings: &DisplaySettings) -> Spans<'static> {
+    Spans::from(Span::styled(
+        format!("Not available: {}", error),
+        Style::default().fg(settings.theme.error),
+    ))
+}
+
+/// Formats seconds since the Unix epoch as an UTC date and time
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let seconds = timestamp % 86_400;
+
+    // converts days since the epoch to a civil date (Howard Hinnant's days_from_civil inverse)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
@@ -1,28 +1,40 @@
-use std::io::Stdout;
-
-use sysinfo::{ProcessExt, System, SystemExt, Uid, UserExt};
-use termion::{event::Key, raw::RawTerminal};
+use super::{
+    centered_rect, process_details::ProcessDetailsView, DisplaySettings, View, ViewContext,
+};
+use crate::core::{send_signal, signal_name, Key, Snapshot, SIGNALS};
+use crate::keymap::{Action, KeyHint};
+use clap::ValueEnum;
+use regex::Regex;
+use serde::Deserialize;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+use sysinfo::{Pid, PidExt, Signal};
 use tui::{
-    backend::TermionBackend,
-    layout::{Constraint, Layout, Rect},
-    style::{Color, Modifier, Style},
-    widgets::{Block, BorderType, Borders, Cell, Row, Table, TableState},
+    backend::Backend,
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{
+        Block, BorderType, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table,
+        TableState,
+    },
     Frame,
 };
 
-const CELL_HEADERS: [&str; 6] = ["PID", "Name", "User", "CPU", "Memory", "Status"];
-
+#[derive(Copy, Clone, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub(crate) enum TableSort {
+    #[default]
+    #[value(alias = "asc")]
+    #[serde(alias = "asc")]
     Ascending,
+    #[value(alias = "desc")]
+    #[serde(alias = "desc")]
     Descending,
 }
 
-impl Default for TableSort {
-    fn default() -> Self {
-        TableSort::Ascending
-    }
-}
-
 impl TableSort {
     fn reverse(&mut self) {
         match self {
@@ -30,10 +42,23 @@ impl TableSort {
             TableSort::Descending => *self = TableSort::Ascending,
         }
     }
+
+    /// Arrow displayed next to the header of the sorted column
+    fn indicator(&self) -> &'static str {
+        match self {
+            TableSort::Ascending => "▲",
+            TableSort::Descending => "▼",
+        }
+    }
 }
 
+/// A column of the process table, the rows can be sorted by any of them
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[allow(clippy::upper_case_acronyms)]
 pub(crate) enum TableSortPredicate {
     PID,
+    #[default]
     Name,
     User,
     CPU,
@@ -41,160 +66,1180 @@ pub(crate) enum TableSortPredicate {
     Status,
 }
 
-impl Default for TableSortPredicate {
-    fn default() -> Self {
-        TableSortPredicate::Name
+impl TableSortPredicate {
+    /// Every column in the default order
+    pub(crate) const ALL: [TableSortPredicate; 6] = [
+        TableSortPredicate::PID,
+        TableSortPredicate::Name,
+        TableSortPredicate::User,
+        TableSortPredicate::CPU,
+        TableSortPredicate::Memory,
+        TableSortPredicate::Status,
+    ];
+
+    fn header(self) -> &'static str {
+        match self {
+            TableSortPredicate::PID => "PID",
+            TableSortPredicate::Name => "Name",
+            TableSortPredicate::User => "User",
+            TableSortPredicate::CPU => "CPU",
+            TableSortPredicate::Memory => "Memory",
+            TableSortPredicate::Status => "Status",
+        }
+    }
+
+    fn width(self, tree_mode: bool) -> Constraint {
+        match self {
+            TableSortPredicate::PID => Constraint::Length(8),
+            // the tree glyphs need room next to the names
+            TableSortPredicate::Name => Constraint::Percentage(if tree_mode { 35 } else { 10 }),
+            TableSortPredicate::User => Constraint::Percentage(5),
+            TableSortPredicate::CPU => Constraint::Length(6),
+            TableSortPredicate::Memory => Constraint::Length(12),
+            TableSortPredicate::Status => Constraint::Length(10),
+        }
+    }
+}
+
+/// Filters the process rows by name, command line, user or PID
+#[derive(Default)]
+struct ProcessFilter {
+    query: String,
+    /// The query is a regular expression instead of a case-insensitive substring
+    use_regex: bool,
+    /// The compiled query, or None if it is not a valid regular expression
+    regex: Option<Regex>,
+}
+
+impl ProcessFilter {
+    fn is_active(&self) -> bool {
+        !self.query.is_empty()
+    }
+
+    fn push(&mut self, ch: char) {
+        self.query.push(ch);
+        self.compile();
+    }
+
+    fn pop(&mut self) {
+        self.query.pop();
+        self.compile();
+    }
+
+    fn toggle_regex(&mut self) {
+        self.use_regex = !self.use_regex;
+        self.compile();
+    }
+
+    fn clear(&mut self) {
+        self.query.clear();
+        self.compile();
+    }
+
+    fn compile(&mut self) {
+        self.regex = if self.use_regex {
+            Regex::new(&self.query).ok()
+        } else {
+            None
+        };
+    }
+
+    fn matches(&self, process: &Process) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        // an invalid regular expression, e.g. while it is being typed, hides nothing
+        if self.use_regex && self.regex.is_none() {
+            return true;
+        }
+
+        let pid = process.pid.to_string();
+        [
+            process.name.as_str(),
+            process.command.as_str(),
+            process.user.as_str(),
+            pid.as_str(),
+        ]
+        .iter()
+        .any(|text| self.find(text).is_some())
+    }
+
+    /// Returns the byte range of the first match in `text`
+    fn find(&self, text: &str) -> Option<(usize, usize)> {
+        if !self.is_active() {
+            return None;
+        }
+
+        if self.use_regex {
+            self.regex
+                .as_ref()
+                .and_then(|regex| regex.find(text))
+                .filter(|found| !found.as_str().is_empty())
+                .map(|found| (found.start(), found.end()))
+        } else {
+            let query = self.query.to_lowercase();
+            // lowercasing can change the byte length of non-ASCII text, in that case
+            // the match is only used for filtering and it is not highlighted
+            let lowercase = text.to_lowercase();
+            lowercase.find(&query).and_then(|start| {
+                let end = start + query.len();
+                if lowercase.len() == text.len()
+                    && text.is_char_boundary(start)
+                    && text.is_char_boundary(end)
+                {
+                    Some((start, end))
+                } else {
+                    None
+                }
+            })
+        }
+    }
+
+    /// Splits `text` into spans with the first match highlighted
+    fn highlight<'a>(&self, text: String, settings: &DisplaySettings) -> Spans<'a> {
+        match self.find(&text) {
+            Some((start, end)) => Spans::from(vec![
+                Span::raw(text[..start].to_string()),
+                Span::styled(
+                    text[start..end].to_string(),
+                    Style::default()
+                        .fg(settings.theme.highlight)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(text[end..].to_string()),
+            ]),
+            None => Spans::from(text),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match (self.use_regex, &self.regex) {
+            (true, Some(_)) => format!("/{}/", self.query),
+            (true, None) => format!("/{}/ (invalid)", self.query),
+            (false, _) => format!("\"{}\"", self.query),
+        }
+    }
+}
+
+/// The process a signal prompt was opened for. The signal goes to it even if the selection
+/// moves to another row in the meantime.
+struct SignalTarget {
+    pid: Pid,
+    name: String,
+}
+
+/// The steps of sending a signal to the selected process
+enum SignalPrompt {
+    /// Choosing the signal from the menu
+    Menu(SignalTarget, ListState),
+    /// Waiting for the confirmation to send the chosen signal
+    Confirm(SignalTarget, Signal),
+}
+
+impl SignalPrompt {
+    fn target(&self) -> &SignalTarget {
+        match self {
+            SignalPrompt::Menu(target, _) | SignalPrompt::Confirm(target, _) => target,
+        }
     }
 }
 
-#[derive(Eq, Ord, PartialEq, PartialOrd)]
 struct Process {
-    pid: String,
+    pid: Pid,
+    parent: Option<Pid>,
     name: String,
+    /// The full command line, only used for filtering
+    command: String,
     user: String,
-    cpu_usage: String,
-    memory_usage: String,
+    cpu_usage: f32,
+    /// Resident memory in KB
+    memory_usage: u64,
     status: String,
+    /// Tree glyphs drawn before the name in tree mode
+    tree_prefix: String,
+    /// Total CPU and memory usage of the process and all of its descendants,
+    /// set only when its subtree is collapsed in tree mode
+    subtree_usage: Option<(f32, u64)>,
 }
 
 pub struct ProcessesView {
+    /// Visible columns, in order
+    columns: Vec<TableSortPredicate>,
     sort_predicate: TableSortPredicate,
     sort_order: TableSort,
     table_state: TableState,
     processes: Vec<Process>,
+    /// The selection follows this process across refreshes and re-sorts
+    selected_pid: Option<Pid>,
+    /// Number of rows visible in the table at the last render, used for paging
+    page_size: usize,
+    signal_prompt: Option<SignalPrompt>,
+    /// Result of the last action, displayed below the table
+    status: Option<String>,
+    /// Children are indented under their parents instead of a flat list
+    tree_mode: bool,
+    /// Processes whose subtree is hidden in tree mode
+    collapsed: HashSet<Pid>,
+    filter: ProcessFilter,
+    /// Set from the command line, unlike `filter` they cannot be cleared in the view
+    pid_filter: Vec<Pid>,
+    user_filter: Option<String>,
+    /// The filter prompt is open and receives the typed characters
+    filter_input: bool,
+    /// Detail pane of the selected process, drawn instead of the table while open
+    details: Option<ProcessDetailsView>,
 }
 
 impl ProcessesView {
     pub(crate) fn new() -> Self {
         ProcessesView {
+            columns: TableSortPredicate::ALL.to_vec(),
             sort_predicate: TableSortPredicate::Name,
             sort_order: TableSort::Ascending,
             table_state: TableState::default(),
-            // TODO: figure out the maximum number or rows to draw
-            processes: Vec::with_capacity(85),
+            processes: Vec::new(),
+            selected_pid: None,
+            page_size: 0,
+            signal_prompt: None,
+            status: None,
+            tree_mode: false,
+            collapsed: HashSet::new(),
+            filter: ProcessFilter::default(),
+            pid_filter: Vec::new(),
+            user_filter: None,
+            filter_input: false,
+            details: None,
+        }
+    }
+
+    /// Moves the selection with the up/down, page and home/end keys,
+    /// left/right changes the sorted column
+    pub(crate) fn handle_arrow_keys(&mut self, key: Key) {
+        match key {
+            Key::Left | Key::Right => {
+                let count = self.columns.len();
+                let position = self
+                    .columns
+                    .iter()
+                    .position(|column| *column == self.sort_predicate)
+                    .unwrap_or(0);
+                let position = if key == Key::Left {
+                    (position + count - 1) % count
+                } else {
+                    (position + 1) % count
+                };
+                self.sort_predicate = self.columns[position];
+                self.sort();
+                return;
+            }
+            _ => (),
+        }
+
+        if self.processes.is_empty() {
+            return;
+        }
+        let last = self.processes.len() - 1;
+        let selected = self.table_state.selected().unwrap_or(0);
+        let page_size = self.page_size.max(1);
+
+        let index = match key {
+            Key::Up => selected.saturating_sub(1),
+            Key::Down => (selected + 1).min(last),
+            Key::PageUp => selected.saturating_sub(page_size),
+            Key::PageDown => (selected + page_size).min(last),
+            Key::Home => 0,
+            Key::End => last,
+            _ => return,
+        };
+        self.select(index);
+    }
+
+    /// Handles the actions of the process table, the others have no meaning in this view
+    pub(crate) fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::ReverseSort => {
+                self.sort_order.reverse();
+                self.sort();
+            }
+            Action::SortByPid => self.sort_by_column(TableSortPredicate::PID),
+            Action::SortByName => self.sort_by_column(TableSortPredicate::Name),
+            Action::SortByUser => self.sort_by_column(TableSortPredicate::User),
+            Action::SortByCpu => self.sort_by_column(TableSortPredicate::CPU),
+            Action::SortByMemory => self.sort_by_column(TableSortPredicate::Memory),
+            Action::SortByStatus => self.sort_by_column(TableSortPredicate::Status),
+            Action::ToggleTree => self.tree_mode = !self.tree_mode,
+            Action::ToggleCollapse if self.tree_mode => {
+                if let Some(pid) = self.selected_pid {
+                    if !self.collapsed.remove(&pid) {
+                        self.collapsed.insert(pid);
+                    }
+                }
+            }
+            Action::Expand if self.tree_mode => {
+                if let Some(pid) = self.selected_pid {
+                    self.collapsed.remove(&pid);
+                }
+            }
+            Action::Collapse if self.tree_mode => {
+                if let Some(pid) = self.selected_pid {
+                    self.collapsed.insert(pid);
+                }
+            }
+            Action::Select => {
+                if let Some(pid) = self.selected_pid {
+                    self.details = Some(ProcessDetailsView::new(pid));
+                    self.status = None;
+                }
+            }
+            Action::Filter => {
+                self.filter_input = true;
+                self.status = None;
+            }
+            Action::KillProcess => {
+                if let Some(process) = self.selected_process() {
+                    let target = SignalTarget {
+                        pid: process.pid,
+                        name: process.name.clone(),
+                    };
+                    let mut menu_state = ListState::default();
+                    menu_state.select(Some(0));
+                    self.signal_prompt = Some(SignalPrompt::Menu(target, menu_state));
+                    self.status = None;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Sorts by a visible column, the sort order is kept
+    fn sort_by_column(&mut self, predicate: TableSortPredicate) {
+        if self.columns.contains(&predicate) {
+            self.sort_predicate = predicate;
+            self.sort();
+        }
+    }
+
+    /// Clears the active filter
+    pub(crate) fn handle_escape(&mut self) {
+        self.filter.clear();
+    }
+
+    /// Returns true while a popup, the filter prompt or the detail pane is open that needs every key press
+    pub(crate) fn is_modal(&self) -> bool {
+        self.signal_prompt.is_some() || self.filter_input || self.details.is_some()
+    }
+
+    /// Returns true while the filter prompt takes the typed characters
+    pub(crate) fn is_filtering(&self) -> bool {
+        self.filter_input
+    }
+
+    /// Handles the key presses while a popup, the filter prompt or the detail pane is open
+    pub(crate) fn handle_modal_key(&mut self, key: Key) {
+        if let Some(details) = &mut self.details {
+            match key {
+                Key::Esc | Key::Char('q') | Key::Char('\n') => self.details = None,
+                _ => details.handle_key(key),
+            }
+            return;
+        }
+
+        if self.filter_input {
+            match key {
+                // Enter keeps the filter, Esc discards it
+                Key::Char('\n') => self.filter_input = false,
+                Key::Esc => {
+                    self.filter.clear();
+                    self.filter_input = false;
+                }
+                Key::Ctrl('r') => self.filter.toggle_regex(),
+                Key::Backspace => self.filter.pop(),
+                Key::Char(ch) => self.filter.push(ch),
+                _ => (),
+            }
+            return;
+        }
+
+        match self.signal_prompt.take() {
+            Some(SignalPrompt::Menu(target, mut menu_state)) => {
+                let selected = menu_state.selected().unwrap_or(0);
+                match key {
+                    Key::Up => menu_state.select(Some(selected.saturating_sub(1))),
+                    Key::Down => menu_state.select(Some((selected + 1).min(SIGNALS.len() - 1))),
+                    Key::Char('\n') => {
+                        self.signal_prompt = Some(SignalPrompt::Confirm(target, SIGNALS[selected]));
+                        return;
+                    }
+                    Key::Esc | Key::Char('q') => return,
+                    _ => (),
+                }
+                self.signal_prompt = Some(SignalPrompt::Menu(target, menu_state));
+            }
+            Some(SignalPrompt::Confirm(target, signal)) => match key {
+                Key::Char('y') | Key::Char('Y') => self.send_signal(target.pid, signal),
+                _ => self.status = Some(format!("Sending {} cancelled", signal_name(signal))),
+            },
+            None => (),
         }
     }
 
-    pub(crate) fn handle_arrow_keys(&self, key: Key) {}
+    fn send_signal(&mut self, pid: Pid, signal: Signal) {
+        self.status = Some(match send_signal(pid, signal) {
+            Ok(()) => format!("Sent {} to {}", signal_name(signal), pid),
+            Err(error) => format!(
+                "Failed to send {} to {}: {}",
+                signal_name(signal),
+                pid,
+                error
+            ),
+        });
+    }
 
-    pub(crate) fn render_processes(
-        &mut self,
-        frame: &mut Frame<TermionBackend<RawTerminal<Stdout>>>,
-        area: Rect,
-        system: &System,
-    ) {
+    fn selected_process(&self) -> Option<&Process> {
+        self.selected_pid
+            .and_then(|pid| self.processes.iter().find(|process| process.pid == pid))
+    }
+
+    fn select(&mut self, index: usize) {
+        self.table_state.select(Some(index));
+        self.selected_pid = self.processes.get(index).map(|process| process.pid);
+    }
+
+    /// Restores the selection on the previously selected process after the rows were rebuilt.
+    /// If that process is gone, the selection stays on the same row.
+    fn restore_selection(&mut self) {
+        if self.processes.is_empty() {
+            self.table_state.select(None);
+            return;
+        }
+
+        let position = self.selected_pid.as_ref().and_then(|pid| {
+            self.processes
+                .iter()
+                .position(|process| process.pid == *pid)
+        });
+        match position {
+            Some(index) => self.table_state.select(Some(index)),
+            None => {
+                let index = self
+                    .table_state
+                    .selected()
+                    .unwrap_or(0)
+                    .min(self.processes.len() - 1);
+                self.select(index);
+            }
+        }
+    }
+
+    /// The process whose details are open, its `/proc` files are read with the next snapshots
+    pub(crate) fn details_pid(&self) -> Option<Pid> {
+        self.details.as_ref().map(ProcessDetailsView::pid)
+    }
+
+    /// Closes the signal prompt if its process has exited
+    pub(crate) fn update(&mut self, snapshot: &Snapshot) {
+        if let Some(prompt) = &self.signal_prompt {
+            let target = prompt.target();
+            if snapshot.process(target.pid).is_none() {
+                self.status = Some(format!(
+                    "{} ({}) exited, no signal was sent",
+                    target.pid, target.name
+                ));
+                self.signal_prompt = None;
+            }
+        }
+    }
+
+    fn render_signal_prompt<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let block = match &self.signal_prompt {
+            Some(prompt) => {
+                let target = prompt.target();
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain)
+                    .title(format!("Send signal to {} ({})", target.pid, target.name))
+            }
+            None => return,
+        };
+
+        match &mut self.signal_prompt {
+            Some(SignalPrompt::Menu(_, menu_state)) => {
+                // borders take 2 lines
+                let popup_area = centered_rect(40, SIGNALS.len() as u16 + 2, area);
+                let items: Vec<ListItem> = SIGNALS
+                    .iter()
+                    .map(|signal| ListItem::new(signal_name(*signal)))
+                    .collect();
+                let menu = List::new(items)
+                    .block(block)
+                    .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                    .highlight_symbol(">> ");
+
+                frame.render_widget(Clear, popup_area);
+                frame.render_stateful_widget(menu, popup_area, menu_state);
+            }
+            Some(SignalPrompt::Confirm(target, signal)) => {
+                let popup_area = centered_rect(40, 4, area);
+                let question = Paragraph::new(Text::from(format!(
+                    "Send {} to {}?\n[y]es / [n]o",
+                    signal_name(*signal),
+                    target.name
+                )))
+                .alignment(Alignment::Center)
+                .block(block);
+
+                frame.render_widget(Clear, popup_area);
+                frame.render_widget(question, popup_area);
+            }
+            None => (),
+        }
+    }
+
+    /// Reorders the sorted rows so that every process follows its parent.
+    /// Siblings keep the order of the current sort.
+    fn build_tree(&mut self) {
+        let pids: HashSet<Pid> = self.processes.iter().map(|process| process.pid).collect();
+        let mut children: HashMap<Pid, Vec<usize>> = HashMap::new();
+        let mut roots = Vec::new();
+
+        for (index, process) in self.processes.iter().enumerate() {
+            match process.parent {
+                Some(parent) if parent != process.pid && pids.contains(&parent) => {
+                    children.entry(parent).or_default().push(index)
+                }
+                _ => roots.push(index),
+            }
+        }
+
+        let tree = ProcessTree {
+            processes: &self.processes,
+            children: &children,
+            collapsed: &self.collapsed,
+        };
+        let mut rows = Vec::with_capacity(self.processes.len());
+        for root in roots {
+            tree.flatten(root, String::new(), String::new(), &mut rows);
+        }
+
+        let mut slots: Vec<Option<Process>> = std::mem::take(&mut self.processes)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.processes = rows
+            .into_iter()
+            .filter_map(|row| {
+                slots[row.index].take().map(|process| Process {
+                    tree_prefix: row.tree_prefix,
+                    subtree_usage: row.subtree_usage,
+                    ..process
+                })
+            })
+            .collect();
+    }
+
+    pub(crate) fn sort_by(&mut self, predicate: TableSortPredicate, order: TableSort) {
+        self.sort_predicate = predicate;
+        self.sort_order = order;
+        self.sort();
+    }
+
+    /// Shows these columns in this order, the rows stay sorted by a visible column
+    pub(crate) fn set_columns(&mut self, columns: Vec<TableSortPredicate>) {
+        if columns.is_empty() {
+            return;
+        }
+        if !columns.contains(&self.sort_predicate) {
+            self.sort_predicate = columns[0];
+        }
+        self.columns = columns;
+    }
+
+    /// Lists only the given processes, or every process if `pids` is empty
+    pub(crate) fn set_pid_filter(&mut self, pids: Vec<Pid>) {
+        self.pid_filter = pids;
+    }
+
+    /// Lists only the processes of `user`
+    pub(crate) fn set_user_filter(&mut self, user: Option<String>) {
+        self.user_filter = user;
+    }
+
+    /// Sorts the rows by the selected column. Rows with equal values are ordered by PID,
+    /// so they keep their place between refreshes.
+    fn sort(&mut self) {
+        let predicate = self.sort_predicate;
+        let order = self.sort_order;
+
+        self.processes.sort_by(|a, b| {
+            let ordering = match predicate {
+                TableSortPredicate::PID => a.pid.cmp(&b.pid),
+                TableSortPredicate::Name => a.name.cmp(&b.name),
+                TableSortPredicate::User => a.user.cmp(&b.user),
+                TableSortPredicate::CPU => a
+                    .cpu_usage
+                    .partial_cmp(&b.cpu_usage)
+                    .unwrap_or(Ordering::Equal),
+                TableSortPredicate::Memory => a.memory_usage.cmp(&b.memory_usage),
+                TableSortPredicate::Status => a.status.cmp(&b.status),
+            }
+            .then_with(|| a.pid.cmp(&b.pid));
+
+            match order {
+                TableSort::Ascending => ordering,
+                TableSort::Descending => ordering.reverse(),
+            }
+        });
+    }
+}
+
+impl<B: Backend> View<B> for ProcessesView {
+    fn actions(&self) -> &'static [(Action, &'static str)] {
+        &[
+            (Action::Up, "previous process"),
+            (Action::Down, "next process"),
+            (Action::PageUp, "page up"),
+            (Action::PageDown, "page down"),
+            (Action::Home, "first process"),
+            (Action::End, "last process"),
+            (Action::Left, "sort by the previous column"),
+            (Action::Right, "sort by the next column"),
+            (Action::SortByPid, "sort by PID"),
+            (Action::SortByName, "sort by name"),
+            (Action::SortByUser, "sort by user"),
+            (Action::SortByCpu, "sort by CPU"),
+            (Action::SortByMemory, "sort by memory"),
+            (Action::SortByStatus, "sort by status"),
+            (Action::ReverseSort, "reverse the sort order"),
+            (Action::Filter, "filter by name, command line, user or PID"),
+            (Action::Cancel, "clear the filter"),
+            (Action::ToggleTree, "show the processes as a tree"),
+            (
+                Action::ToggleCollapse,
+                "collapse or expand the subtree in tree mode",
+            ),
+            (Action::Expand, "expand the subtree in tree mode"),
+            (Action::Collapse, "collapse the subtree in tree mode"),
+            (Action::Select, "details of the selected process"),
+            (Action::KillProcess, "send a signal to the selected process"),
+        ]
+    }
+
+    /// The keys of the open popup, or the main actions of the table
+    fn key_hints(&self) -> Vec<KeyHint> {
+        if self.details.is_some() {
+            return vec![
+                KeyHint::Key("←→", "pane"),
+                KeyHint::Key("↑↓", "scroll"),
+                KeyHint::Key("Esc", "close"),
+            ];
+        }
+        if self.filter_input {
+            return vec![
+                KeyHint::Key("Enter", "keep filter"),
+                KeyHint::Key("Esc", "clear filter"),
+                KeyHint::Key("Ctrl-r", "regex"),
+            ];
+        }
+        match self.signal_prompt {
+            Some(SignalPrompt::Menu(..)) => {
+                return vec![
+                    KeyHint::Key("↑↓", "choose"),
+                    KeyHint::Key("Enter", "select"),
+                    KeyHint::Key("Esc", "cancel"),
+                ]
+            }
+            Some(SignalPrompt::Confirm(..)) => {
+                return vec![
+                    KeyHint::Key("y", "send"),
+                    KeyHint::Key("any other key", "cancel"),
+                ]
+            }
+            None => (),
+        }
+
+        let mut hints = vec![
+            KeyHint::Action(Action::Filter, "filter"),
+            KeyHint::Action(Action::Right, "sort column"),
+            KeyHint::Action(Action::ReverseSort, "reverse"),
+            KeyHint::Action(Action::ToggleTree, "tree"),
+        ];
+        if self.tree_mode {
+            hints.push(KeyHint::Action(Action::ToggleCollapse, "collapse"));
+        }
+        if self.filter.is_active() {
+            hints.push(KeyHint::Action(Action::Cancel, "clear filter"));
+        }
+        hints.push(KeyHint::Action(Action::Select, "details"));
+        hints.push(KeyHint::Action(Action::KillProcess, "kill"));
+        hints
+    }
+
+    fn render(&mut self, frame: &mut Frame<B>, area: Rect, context: &ViewContext) {
+        let snapshot = context.snapshot;
+        let settings = context.settings;
+        if let Some(details) = &mut self.details {
+            details.render(frame, area, snapshot, context.history, settings);
+            return;
+        }
+
+        let status_height = if self.status.is_some() || self.filter_input {
+            1
+        } else {
+            0
+        };
         let process_layout = Layout::default()
-            .constraints([Constraint::Percentage(100)].as_ref())
+            .constraints([Constraint::Min(3), Constraint::Length(status_height)].as_ref())
             .split(area);
 
         let selected_style = Style::default().add_modifier(Modifier::REVERSED);
-        let normal_style = Style::default().bg(Color::Blue);
-        let header_cells = CELL_HEADERS.iter().map(|header| Cell::from(*header));
+        let normal_style = settings.header_style();
+        let header_cells = self.columns.iter().map(|column| {
+            if *column == self.sort_predicate {
+                Cell::from(format!(
+                    "{} {}",
+                    column.header(),
+                    self.sort_order.indicator()
+                ))
+                .style(Style::default().add_modifier(Modifier::BOLD))
+            } else {
+                Cell::from(column.header())
+            }
+        });
         let table_header = Row::new(header_cells).style(normal_style).height(1);
-        let values = system.processes().clone();
 
-        self.processes = values
+        // collecting from the snapshot sizes the rows to its process count
+        self.processes = snapshot
+            .processes
             .iter()
-            .map(|(_pid, process)| Process {
-                pid: process.pid().clone().to_string(),
-                name: process.name().to_string(),
-                user: get_username_for_id(process.user_id(), system),
-                cpu_usage: process.cpu_usage().to_string(),
-                memory_usage: process.memory().to_string(),
-                status: process.status().to_string(),
+            .map(|process| Process {
+                command: process.command.join(" "),
+                pid: process.pid,
+                parent: process.parent,
+                name: process.name.clone(),
+                user: process.user.clone(),
+                cpu_usage: process.cpu_usage,
+                memory_usage: process.memory,
+                status: process.status.clone(),
+                tree_prefix: String::new(),
+                subtree_usage: None,
             })
             .collect();
+        let filter = &self.filter;
+        let pid_filter = &self.pid_filter;
+        let user_filter = &self.user_filter;
+        self.processes.retain(|process| {
+            (pid_filter.is_empty() || pid_filter.contains(&process.pid))
+                && user_filter
+                    .as_ref()
+                    .is_none_or(|user| process.user == *user)
+                && filter.matches(process)
+        });
 
         self.sort();
+        if self.tree_mode {
+            self.build_tree();
+        }
+        self.restore_selection();
+        // the table's borders and header take 3 lines
+        self.page_size = process_layout[0].height.saturating_sub(3) as usize;
 
-        /*let rows = system.processes().iter().map(|(pid, process)| {
-            let cells: [Cell; 6] = [
-                Cell::from(pid.to_string()),
-                Cell::from(process.name()),
-                Cell::from(get_username_for_id(process.user_id(), system)),
-                Cell::from(process.cpu_usage().to_string()),
-                Cell::from(process.memory().to_string()),
-                Cell::from(process.status().to_string()),
-            ];
-            Row::new(cells).height(1)
-        });*/
         let rows = self.processes.iter().map(|p| {
-            let cells: [Cell; 6] = [
-                Cell::from(p.pid.clone()),
-                Cell::from(p.name.clone()),
-                Cell::from(p.user.clone()),
-                Cell::from(p.cpu_usage.clone()),
-                Cell::from(p.memory_usage.clone()),
-                Cell::from(p.status.clone()),
-            ];
+            // the tree lines are drawn in front of the name, the filter only matches the name itself
+            let (prefix, cpu_usage, memory_usage) = match p.subtree_usage {
+                Some((cpu_usage, memory_usage)) => {
+                    (format!("{}[+] ", p.tree_prefix), cpu_usage, memory_usage)
+                }
+                None => (p.tree_prefix.clone(), p.cpu_usage, p.memory_usage),
+            };
+            let cells = self.columns.iter().map(|column| match column {
+                TableSortPredicate::PID => {
+                    Cell::from(self.filter.highlight(p.pid.as_u32().to_string(), settings))
+                }
+                TableSortPredicate::Name => {
+                    let mut name = self.filter.highlight(p.name.clone(), settings);
+                    name.0.insert(0, Span::raw(prefix.clone()));
+                    Cell::from(name)
+                }
+                TableSortPredicate::User => {
+                    Cell::from(self.filter.highlight(p.user.clone(), settings))
+                }
+                TableSortPredicate::CPU => Cell::from(format!("{:.1}", cpu_usage)),
+                TableSortPredicate::Memory => Cell::from(settings.format_kib(memory_usage)),
+                TableSortPredicate::Status => Cell::from(p.status.clone()),
+            });
             Row::new(cells).height(1)
         });
 
+        let widths: Vec<Constraint> = self
+            .columns
+            .iter()
+            .map(|column| column.width(self.tree_mode))
+            .collect();
+        let mut title = String::from(if self.tree_mode {
+            "Processes (tree)"
+        } else {
+            "Processes"
+        });
+        if !self.pid_filter.is_empty() {
+            let pids: Vec<String> = self.pid_filter.iter().map(Pid::to_string).collect();
+            title.push_str(&format!(" - PID: {}", pids.join(",")));
+        }
+        if let Some(user) = &self.user_filter {
+            title.push_str(&format!(" - user: {}", user));
+        }
+        if self.filter.is_active() {
+            title.push_str(&format!(" - filter: {}", self.filter.describe()));
+        }
         let table = Table::new(rows)
             .header(table_header)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .border_type(BorderType::Plain)
-                    .title("Processes"),
+                    .title(title),
             )
             .highlight_style(selected_style)
             .highlight_symbol(">> ")
-            .widths(&[
-                Constraint::Length(4),
-                Constraint::Percentage(10),
-                Constraint::Percentage(5),
-                Constraint::Length(3),
-                Constraint::Length(15),
-                Constraint::Length(10),
-            ]);
+            .widths(&widths);
 
         frame.render_stateful_widget(table, process_layout[0], &mut self.table_state);
+
+        if self.filter_input {
+            let mode = if self.filter.use_regex {
+                "regex"
+            } else {
+                "text"
+            };
+            let prompt = format!("/{}", self.filter.query);
+            let hint = format!(
+                "  ({}, Ctrl-R: toggle regex, Enter: apply, Esc: clear)",
+                mode
+            );
+            let cursor_x = process_layout[1].x + prompt.chars().count() as u16;
+            frame.render_widget(
+                Paragraph::new(Spans::from(vec![
+                    Span::raw(prompt),
+                    Span::styled(hint, Style::default().add_modifier(Modifier::DIM)),
+                ])),
+                process_layout[1],
+            );
+            frame.set_cursor(cursor_x, process_layout[1].y);
+        } else if let Some(status) = &self.status {
+            frame.render_widget(Paragraph::new(status.as_str()), process_layout[1]);
+        }
+        self.render_signal_prompt(frame, area);
     }
+}
 
-    pub(crate) fn sort_by(&mut self, predicate: TableSortPredicate) {
-        self.sort_predicate = predicate;
-        self.sort();
+/// Position of a process row in the flattened tree
+struct TreeRow {
+    index: usize,
+    tree_prefix: String,
+    subtree_usage: Option<(f32, u64)>,
+}
+
+/// Parent-child relations of the process rows, used to flatten them in tree order
+struct ProcessTree<'a> {
+    processes: &'a [Process],
+    children: &'a HashMap<Pid, Vec<usize>>,
+    collapsed: &'a HashSet<Pid>,
+}
+
+impl ProcessTree<'_> {
+    /// Appends the row at `index` and, unless it is collapsed, its descendants to `rows`.
+    /// `prefix` is drawn before the row's own name, `child_prefix` before its children's glyphs.
+    fn flatten(&self, index: usize, prefix: String, child_prefix: String, rows: &mut Vec<TreeRow>) {
+        let pid = self.processes[index].pid;
+        let children = self.children.get(&pid).map(Vec::as_slice).unwrap_or(&[]);
+
+        if self.collapsed.contains(&pid) && !children.is_empty() {
+            rows.push(TreeRow {
+                index,
+                tree_prefix: prefix,
+                subtree_usage: Some(self.subtree_usage(index)),
+            });
+            return;
+        }
+        rows.push(TreeRow {
+            index,
+            tree_prefix: prefix,
+            subtree_usage: None,
+        });
+
+        for (position, child) in children.iter().enumerate() {
+            let is_last = position == children.len() - 1;
+            let (glyph, continuation) = if is_last {
+                ("└─ ", "   ")
+            } else {
+                ("├─ ", "│  ")
+            };
+            self.flatten(
+                *child,
+                format!("{}{}", child_prefix, glyph),
+                format!("{}{}", child_prefix, continuation),
+                rows,
+            );
+        }
     }
 
-    fn sort(&mut self) {
-        match self.sort_predicate {
-            TableSortPredicate::PID => self.processes.sort_by(|a, b| match self.sort_order {
-                TableSort::Ascending => a.pid.cmp(&b.pid),
-                TableSort::Descending => a.pid.cmp(&b.pid).reverse(),
-            }),
-            TableSortPredicate::Name => self.processes.sort_by(|a, b| match self.sort_order {
-                TableSort::Ascending => a.name.cmp(&b.name),
-                TableSort::Descending => a.name.cmp(&b.name).reverse(),
-            }),
-            TableSortPredicate::User => self.processes.sort_by(|a, b| match self.sort_order {
-                TableSort::Ascending => a.user.cmp(&b.user),
-                TableSort::Descending => a.user.cmp(&b.user).reverse(),
-            }),
-            TableSortPredicate::CPU => self.processes.sort_by(|a, b| match self.sort_order {
-                TableSort::Ascending => a.cpu_usage.cmp(&b.cpu_usage),
-                TableSort::Descending => a.cpu_usage.cmp(&b.cpu_usage).reverse(),
-            }),
-            TableSortPredicate::Memory => self.processes.sort_by(|a, b| match self.sort_order {
-                TableSort::Ascending => a.memory_usage.cmp(&b.memory_usage),
-                TableSort::Descending => a.memory_usage.cmp(&b.memory_usage).reverse(),
-            }),
-            TableSortPredicate::Status => self.processes.sort_by(|a, b| match self.sort_order {
-                TableSort::Ascending => a.status.cmp(&b.status),
-                TableSort::Descending => a.status.cmp(&b.status).reverse(),
-            }),
-        }
-    }
-}
-
-fn get_username_for_id(uid: Option<&Uid>, sysinfo: &System) -> String {
-    if let Some(uid) = uid {
-        if let Some(user) = sysinfo.get_user_by_id(&uid) {
-            return user.name().to_string();
-        } else {
-            return String::from("N/A");
+    /// Sums the CPU and memory usage of a process and all of its descendants
+    fn subtree_usage(&self, index: usize) -> (f32, u64) {
+        let process = &self.processes[index];
+        let children = self
+            .children
+            .get(&process.pid)
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+
+        children.iter().fold(
+            (process.cpu_usage, process.memory_usage),
+            |(cpu_usage, memory_usage), child| {
+                let (child_cpu_usage, child_memory_usage) = self.subtree_usage(*child);
+                (
+                    cpu_usage + child_cpu_usage,
+                    memory_usage + child_memory_usage,
+                )
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProcessesView, TableSort, TableSortPredicate};
+    use crate::core::{
+        HistoryConfig, Key, MetricsHistory, MetricsSource, ProcessDetailsSnapshot, ScriptedSource,
+        SnapshotRequest,
+    };
+    use crate::keymap::{Action, KeyHint};
+    use crate::ui::testing::{find_line, lines, render_view, render_view_buffer, snapshot};
+    use crate::ui::{DisplaySettings, View};
+    use std::time::Instant;
+    use sysinfo::{Pid, PidExt};
+    use tui::backend::TestBackend;
+
+    #[test]
+    fn lists_processes_sorted_by_name() {
+        let snapshot = snapshot();
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let mut process_view = ProcessesView::new();
+
+        let lines = render_view(120, 20, &mut process_view, &snapshot, &history);
+
+        let backup = find_line(&lines, "7        backup");
+        let editor = find_line(&lines, "42       editor");
+        let init = find_line(&lines, "1        init");
+        assert!(backup < editor && editor < init);
+        assert!(lines[editor].contains("alice"));
+        assert!(lines[editor].contains("100.00 MiB"));
+        // the first row is selected
+        assert!(lines[backup].contains(">> 7"));
+    }
+
+    #[test]
+    fn sorts_by_cpu_usage_in_reverse() {
+        let snapshot = snapshot();
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let mut process_view = ProcessesView::new();
+        // Name -> User -> CPU
+        process_view.handle_arrow_keys(Key::Right);
+        process_view.handle_arrow_keys(Key::Right);
+        process_view.handle_action(Action::ReverseSort);
+
+        let lines = render_view(120, 20, &mut process_view, &snapshot, &history);
+
+        let editor = find_line(&lines, "editor");
+        let backup = find_line(&lines, "backup");
+        let init = find_line(&lines, "init");
+        assert!(editor < backup && backup < init);
+    }
+
+    #[test]
+    fn sorts_as_given_on_the_command_line() {
+        let snapshot = snapshot();
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let mut process_view = ProcessesView::new();
+        process_view.sort_by(TableSortPredicate::Memory, TableSort::Descending);
+
+        let lines = render_view(120, 20, &mut process_view, &snapshot, &history);
+
+        find_line(&lines, "Memory ▼");
+        let editor = find_line(&lines, "editor");
+        let backup = find_line(&lines, "backup");
+        let init = find_line(&lines, "init");
+        assert!(editor < backup && backup < init);
+    }
+
+    #[test]
+    fn filters_processes_by_name() {
+        let snapshot = snapshot();
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let mut process_view = ProcessesView::new();
+        process_view.handle_action(Action::Filter);
+        for ch in "edi".chars() {
+            process_view.handle_modal_key(Key::Char(ch));
         }
-    } else {
-        return String::from("N/A");
+        process_view.handle_modal_key(Key::Char('\n'));
+
+        let lines = render_view(120, 20, &mut process_view, &snapshot, &history);
+
+        find_line(&lines, "editor");
+        assert!(!lines.iter().any(|line| line.contains("backup")));
+        assert!(!lines.iter().any(|line| line.contains("init")));
+    }
+
+    #[test]
+    fn lists_only_the_processes_of_the_command_line_filters() {
+        let snapshot = snapshot();
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let mut process_view = ProcessesView::new();
+        process_view.set_pid_filter(vec![Pid::from_u32(1), Pid::from_u32(42)]);
+        process_view.set_user_filter(Some(String::from("alice")));
+
+        let lines = render_view(120, 20, &mut process_view, &snapshot, &history);
+
+        find_line(&lines, "Processes - PID: 1,42 - user: alice");
+        find_line(&lines, "editor");
+        assert!(!lines.iter().any(|line| line.contains("backup")));
+        assert!(!lines.iter().any(|line| line.contains("init")));
+    }
+
+    #[test]
+    fn shows_details_of_the_selected_process_after_the_next_snapshot() {
+        let mut with_details = snapshot();
+        with_details.process_details = Some(ProcessDetailsSnapshot {
+            pid: Pid::from_u32(42),
+            environment: vec![String::from("EDITOR=vi")],
+            threads: Ok(3),
+            limits: Ok(Vec::new()),
+            open_files: Ok(Vec::new()),
+            memory_maps: Err(String::from("permission denied")),
+        });
+        let mut source = ScriptedSource::new(vec![snapshot(), with_details]);
+        let mut history = MetricsHistory::new(HistoryConfig::default());
+        let snapshot = source.collect(&SnapshotRequest::default());
+        history.record(&snapshot, Instant::now());
+        let mut process_view = ProcessesView::new();
+        render_view(120, 20, &mut process_view, &snapshot, &history);
+        process_view.handle_arrow_keys(Key::Down);
+        process_view.handle_action(Action::Select);
+        assert_eq!(process_view.details_pid(), Some(Pid::from_u32(42)));
+
+        let lines = render_view(120, 20, &mut process_view, &snapshot, &history);
+        find_line(&lines, "Loading…");
+
+        let snapshot = source.collect(&SnapshotRequest::default());
+        history.record(&snapshot, Instant::now());
+        process_view.update(&snapshot);
+        let lines = render_view(120, 20, &mut process_view, &snapshot, &history);
+        find_line(&lines, "Process 42 (editor)");
+        find_line(&lines, "/usr/bin/editor --test");
+        find_line(&lines, "1 (init)");
+        assert!(lines[find_line(&lines, "Threads")].contains('3'));
+        find_line(&lines, "min 12.5%  avg 12.5%  max 12.5%");
+    }
+
+    #[test]
+    fn the_key_hints_follow_the_mode() {
+        let mut process_view = ProcessesView::new();
+        let hints = |view: &ProcessesView| View::<TestBackend>::key_hints(view);
+
+        assert!(hints(&process_view).contains(&KeyHint::Action(Action::Filter, "filter")));
+        assert!(!hints(&process_view).contains(&KeyHint::Action(Action::Cancel, "clear filter")));
+
+        process_view.handle_action(Action::Filter);
+        assert_eq!(
+            hints(&process_view)[0],
+            KeyHint::Key("Enter", "keep filter")
+        );
+
+        process_view.handle_modal_key(Key::Char('x'));
+        process_view.handle_modal_key(Key::Char('\n'));
+        assert!(hints(&process_view).contains(&KeyHint::Action(Action::Cancel, "clear filter")));
+    }
+
+    #[test]
+    fn closes_the_signal_prompt_when_its_process_exits() {
+        let snapshot = snapshot();
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let mut process_view = ProcessesView::new();
+        render_view(120, 20, &mut process_view, &snapshot, &history);
+        // backup, then editor
+        process_view.handle_arrow_keys(Key::Down);
+        process_view.handle_action(Action::KillProcess);
+        process_view.handle_modal_key(Key::Char('\n'));
+        let lines = render_view(120, 20, &mut process_view, &snapshot, &history);
+        find_line(&lines, "Send signal to 42 (editor)");
+
+        let mut exited = snapshot.clone();
+        exited
+            .processes
+            .retain(|process| process.pid != Pid::from_u32(42));
+        process_view.update(&exited);
+
+        assert!(!process_view.is_modal());
+        let lines = render_view(120, 20, &mut process_view, &exited, &history);
+        find_line(&lines, "42 (editor) exited, no signal was sent");
+        assert!(!lines.iter().any(|line| line.contains("Send signal")));
+    }
+
+    #[test]
+    fn highlights_the_filter_match_in_the_name_of_a_tree_row() {
+        let snapshot = snapshot();
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let mut process_view = ProcessesView::new();
+        process_view.handle_action(Action::ToggleTree);
+        process_view.handle_action(Action::Filter);
+        process_view.handle_modal_key(Key::Ctrl('r'));
+        for ch in "^(init|edit)".chars() {
+            process_view.handle_modal_key(Key::Char(ch));
+        }
+        process_view.handle_modal_key(Key::Char('\n'));
+
+        let buffer = render_view_buffer(120, 20, &mut process_view, &snapshot, &history);
+
+        // editor is drawn under init, the anchored match starts after the tree lines in front of it
+        let width = buffer.area.width as usize;
+        let y = find_line(&lines(&buffer), "editor");
+        let row = &buffer.content()[y * width..(y + 1) * width];
+        let highlighted: Vec<usize> = (0..row.len())
+            .filter(|&index| row[index].fg == DisplaySettings::DEFAULT.theme.highlight)
+            .collect();
+        let name: String = row[highlighted[0]..]
+            .iter()
+            .map(|cell| cell.symbol.as_str())
+            .collect();
+        assert_eq!(highlighted.len(), 4);
+        assert!(name.starts_with("editor"));
+        assert_eq!(row[highlighted[0] - 3].symbol, "└");
+    }
+
+    #[test]
+    fn expands_and_collapses_the_subtree_of_the_selected_process() {
+        let snapshot = snapshot();
+        let history = MetricsHistory::new(HistoryConfig::default());
+        let mut process_view = ProcessesView::new();
+        process_view.handle_action(Action::ToggleTree);
+        let shows_children = |process_view: &mut ProcessesView| {
+            let lines = render_view(120, 20, process_view, &snapshot, &history);
+            lines.iter().any(|line| line.contains("editor"))
+        };
+        // init is the first row of the tree and selected
+        assert!(shows_children(&mut process_view));
+
+        // collapsing and expanding twice does not toggle back
+        for _ in 0..2 {
+            process_view.handle_action(Action::Collapse);
+            assert!(!shows_children(&mut process_view));
+        }
+        for _ in 0..2 {
+            process_view.handle_action(Action::Expand);
+            assert!(shows_children(&mut process_view));
+        }
+
+        process_view.handle_action(Action::ToggleCollapse);
+        assert!(!shows_children(&mut process_view));
+        process_view.handle_action(Action::ToggleCollapse);
+        assert!(shows_children(&mut process_view));
     }
 }
@@ -0,0 +1,241 @@
+use super::{DisplaySettings, View, ViewContext};
+use crate::core::{Key, Sensor, SensorChip, SensorKind};
+use crate::keymap::Action;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::Text,
+    widgets::{Block, BorderType, Borders, Gauge, Paragraph},
+    Frame,
+};
+
+pub struct SensorsView {
+    /// Number of chips at the last rendering
+    chip_count: usize,
+    /// Index of the first chip drawn, the chips rarely fit on one screen
+    first_chip: usize,
+}
+
+impl SensorsView {
+    pub fn new() -> Self {
+        SensorsView {
+            chip_count: 0,
+            first_chip: 0,
+        }
+    }
+
+    fn render_chip<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        chip: &SensorChip,
+        area: Rect,
+        settings: &DisplaySettings,
+    ) {
+        let block = Block::default()
+            .title(chip.name.clone())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+        let inner_area = block.inner(area);
+        frame.render_widget(block, area);
+
+        let row_areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1); chip.sensors.len()])
+            .split(inner_area);
+
+        for (sensor, row_area) in chip.sensors.iter().zip(row_areas) {
+            let sensor_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(
+                    [
+                        Constraint::Length(24),
+                        Constraint::Length(14),
+                        Constraint::Min(10),
+                        Constraint::Length(30),
+                    ]
+                    .as_ref(),
+                )
+                .split(row_area);
+
+            let format = |value: f64| format_reading(sensor.kind, value);
+            let limits = match (sensor.high, sensor.critical) {
+                (Some(high), Some(critical)) => {
+                    format!(" high {} crit {}", format(high), format(critical))
+                }
+                (Some(high), None) => format!(" high {}", format(high)),
+                (None, Some(critical)) => format!(" crit {}", format(critical)),
+                (None, None) => String::new(),
+            };
+            frame.render_widget(Paragraph::new(sensor.label.clone()), sensor_layout[0]);
+            frame.render_widget(Paragraph::new(format(sensor.value)), sensor_layout[1]);
+            frame.render_widget(Paragraph::new(limits), sensor_layout[3]);
+
+            if let Some(percent) = limit_percent(sensor) {
+                let gauge = Gauge::default()
+                    .percent(percent)
+                    .gauge_style(Style::default().fg(settings.color_for_percent(percent)));
+                frame.render_widget(gauge, sensor_layout[2]);
+            }
+        }
+    }
+
+    /// Scrolls through the chips
+    pub(crate) fn handle_arrow_keys(&mut self, key: Key) {
+        let last = self.chip_count.saturating_sub(1);
+        match key {
+            Key::Up => self.first_chip = self.first_chip.saturating_sub(1),
+            Key::Down => self.first_chip = (self.first_chip + 1).min(last),
+            Key::Home => self.first_chip = 0,
+            Key::End => self.first_chip = last,
+            _ => (),
+        }
+    }
+}
+
+impl<B: Backend> View<B> for SensorsView {
+    fn actions(&self) -> &'static [(Action, &'static str)] {
+        &[
+            (Action::Up, "scroll up"),
+            (Action::Down, "scroll down"),
+            (Action::Home, "first chip"),
+            (Action::End, "last chip"),
+        ]
+    }
+
+    /// Renders every chip with a gauge of each sensor against its critical or maximum limit
+    fn render(&mut self, frame: &mut Frame<B>, area: Rect, context: &ViewContext) {
+        let sensors = &context.snapshot.sensors;
+        self.chip_count = sensors.chips.len();
+        self.first_chip = self.first_chip.min(self.chip_count.saturating_sub(1));
+        if sensors.chips.is_empty() {
+            let message = match &sensors.error {
+                Some(error) => format!("Cannot read {}: {}", sensors.root.display(), error),
+                None => format!("No sensors found in {}", sensors.root.display()),
+            };
+            let block = Block::default()
+                .title("Sensors")
+                .borders(Borders::ALL)
+                .border_type(BorderType::Plain);
+            frame.render_widget(Paragraph::new(Text::from(message)).block(block), area);
+            return;
+        }
+
+        let chips = &sensors.chips[self.first_chip..];
+        // borders take 2 lines, the remaining space is left empty
+        let mut constraints: Vec<Constraint> = chips
+            .iter()
+            .map(|chip| Constraint::Length(chip.sensors.len() as u16 + 2))
+            .collect();
+        constraints.push(Constraint::Min(0));
+        let chip_areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        for (chip, chip_area) in chips.iter().zip(chip_areas) {
+            if chip_area.height == 0 {
+                break;
+            }
+            self.render_chip(frame, chip, chip_area, context.settings);
+        }
+    }
+}
+
+/// Formats a reading with its unit and a precision suiting the kind of the sensor
+fn format_reading(kind: SensorKind, value: f64) -> String {
+    match kind {
+        SensorKind::Fan => format!("{:.0} {}", value, kind.unit()),
+        SensorKind::Voltage | SensorKind::Current => format!("{:.2} {}", value, kind.unit()),
+        SensorKind::Temperature | SensorKind::Power => format!("{:.1} {}", value, kind.unit()),
+    }
+}
+
+/// Returns how close the sensor is to its critical limit, or to its maximum without one.
+/// Sensors without limits get no gauge.
+fn limit_percent(sensor: &Sensor) -> Option<u16> {
+    let limit = sensor
+        .critical
+        .or(sensor.high)
+        .filter(|limit| *limit > 0.0)?;
+    Some((sensor.value / limit * 100.0).clamp(0.0, 100.0) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SensorsView;
+    use crate::core::{read_sensors, HistoryConfig, MetricsHistory, Snapshot};
+    use crate::ui::testing::{find_line, lines, render_view_buffer};
+    use crate::ui::DisplaySettings;
+    use std::{fs, path::Path};
+
+    fn write_chip(root: &Path, directory: &str, attributes: &[(&str, &str)]) {
+        let directory = root.join(directory);
+        fs::create_dir(&directory).unwrap();
+        for (attribute, value) in attributes {
+            fs::write(directory.join(attribute), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn groups_the_sensors_by_chip_with_gauges_against_their_limits() {
+        let root = tempfile::tempdir().unwrap();
+        write_chip(
+            root.path(),
+            "hwmon0",
+            &[
+                ("name", "nvme"),
+                ("temp1_input", "60000"),
+                ("temp1_label", "Composite"),
+                ("temp1_max", "80000"),
+            ],
+        );
+        write_chip(
+            root.path(),
+            "hwmon1",
+            &[
+                ("name", "coretemp"),
+                ("temp1_input", "45000"),
+                ("temp1_label", "Package id 0"),
+                ("temp1_crit", "100000"),
+                ("temp2_input", "95000"),
+                ("temp2_label", "Core 0"),
+                ("temp2_max", "80000"),
+                ("temp2_crit", "100000"),
+                ("fan1_input", "1200"),
+            ],
+        );
+        let snapshot = Snapshot {
+            sensors: read_sensors(root.path()),
+            ..Snapshot::default()
+        };
+        let history = MetricsHistory::new(HistoryConfig::default());
+
+        let buffer = render_view_buffer(120, 20, &mut SensorsView::new(), &snapshot, &history);
+        let lines = lines(&buffer);
+
+        // the chips are sorted by name, each one lists its own sensors
+        let coretemp = find_line(&lines, "coretemp");
+        let package = find_line(&lines, "Package id 0");
+        let core = find_line(&lines, "Core 0");
+        let fan = find_line(&lines, "fan1");
+        let nvme = find_line(&lines, "nvme");
+        let composite = find_line(&lines, "Composite");
+        assert!(coretemp < package && package < core && core < fan && fan < nvme);
+        assert!(nvme < composite);
+
+        // the gauges start after the label and the value, their filled part has the colour as background
+        let gauge = |y: usize| buffer.get(39, y as u16).bg;
+        assert!(lines[package].contains("45%"));
+        assert_eq!(gauge(package), DisplaySettings::DEFAULT.theme.normal);
+        assert!(lines[core].contains("95%"));
+        assert!(lines[core].contains("high 80.0 °C crit 100.0 °C"));
+        assert_eq!(gauge(core), DisplaySettings::DEFAULT.theme.critical);
+        // without a critical limit the maximum is the limit
+        assert!(lines[composite].contains("75%"));
+        assert_eq!(gauge(composite), DisplaySettings::DEFAULT.theme.high);
+        // a sensor without limits has no gauge
+        assert!(!lines[fan].contains('%'));
+        assert!(lines[fan].contains("1200 RPM"));
+    }
+}
@@ -0,0 +1,249 @@
+use super::{DisplaySettings, View, ViewContext};
+use crate::core::{InodeUsage, Metric, MetricsHistory, Snapshot};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::Span,
+    widgets::{Block, BorderType, Borders, Cell, Gauge, Paragraph, Row, Table},
+    Frame,
+};
+
+const FILESYSTEM_HEADERS: [&str; 5] = ["Mount point", "Type", "Device", "Space", "Inodes"];
+
+const IO_CELL_HEADERS: [&str; 6] = [
+    "Device",
+    "Read/s",
+    "Write/s",
+    "Total read",
+    "Total written",
+    "Busy",
+];
+
+/// A mounted filesystem with its space and inode usage.
+struct Filesystem {
+    device: String,
+    fs_type: String,
+    mount_point: String,
+    total_space: u64,
+    available_space: u64,
+    inodes: Option<InodeUsage>,
+}
+
+impl Filesystem {
+    fn used_space(&self) -> u64 {
+        self.total_space.saturating_sub(self.available_space)
+    }
+
+    fn used_percent(&self) -> f64 {
+        if self.total_space == 0 {
+            0.0
+        } else {
+            self.used_space() as f64 / self.total_space as f64 * 100.0
+        }
+    }
+}
+
+/// Throughput of a block device between the last two snapshots.
+struct DiskThroughput {
+    name: String,
+    read_per_sec: f64,
+    written_per_sec: f64,
+    total_read: u64,
+    total_written: u64,
+    busy_percent: f64,
+}
+
+pub struct StorageView {
+    filesystems: Vec<Filesystem>,
+    throughputs: Vec<DiskThroughput>,
+}
+
+impl StorageView {
+    pub fn new() -> Self {
+        StorageView {
+            filesystems: Vec::new(),
+            throughputs: Vec::new(),
+        }
+    }
+
+    fn render_filesystems<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        settings: &DisplaySettings,
+    ) {
+        let block = Block::default()
+            .title("Filesystems")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+        let inner_area = block.inner(area);
+        frame.render_widget(block, area);
+
+        let mut constraints = vec![Constraint::Length(1); self.filesystems.len() + 1];
+        constraints.push(Constraint::Min(0));
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(inner_area);
+
+        let header_style = Style::default().add_modifier(Modifier::BOLD);
+        let header = filesystem_row_layout(rows[0]);
+        for (title, column) in FILESYSTEM_HEADERS.iter().zip(header) {
+            frame.render_widget(Paragraph::new(Span::styled(*title, header_style)), column);
+        }
+
+        for (filesystem, row) in self.filesystems.iter().zip(rows.iter().skip(1)) {
+            let columns = filesystem_row_layout(*row);
+            let used_percent = filesystem.used_percent() as u16;
+
+            let space_bar = Gauge::default()
+                .percent(used_percent.min(100))
+                .label(format!(
+                    "{} / {} ({}%)",
+                    settings.format_bytes(filesystem.used_space()),
+                    settings.format_bytes(filesystem.total_space),
+                    used_percent
+                ))
+                .gauge_style(Style::default().fg(settings.color_for_percent(used_percent)));
+
+            frame.render_widget(Paragraph::new(filesystem.mount_point.as_str()), columns[0]);
+            frame.render_widget(Paragraph::new(filesystem.fs_type.as_str()), columns[1]);
+            frame.render_widget(Paragraph::new(filesystem.device.as_str()), columns[2]);
+            frame.render_widget(space_bar, columns[3]);
+
+            match filesystem.inodes {
+                Some(inodes) if inodes.total > 0 => {
+                    let inode_percent = inodes.used_percent() as u16;
+                    let inode_bar = Gauge::default()
+                        .percent(inode_percent.min(100))
+                        .label(format!("{}%", inode_percent))
+                        .gauge_style(
+                            Style::default().fg(settings.color_for_percent(inode_percent)),
+                        );
+                    frame.render_widget(inode_bar, columns[4]);
+                }
+                _ => frame.render_widget(Paragraph::new("N/A"), columns[4]),
+            }
+        }
+    }
+
+    fn render_throughputs<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        settings: &DisplaySettings,
+    ) {
+        let header_cells = IO_CELL_HEADERS.iter().map(|header| Cell::from(*header));
+        let table_header = Row::new(header_cells)
+            .style(settings.header_style())
+            .height(1);
+
+        let rows = self.throughputs.iter().map(|throughput| {
+            let cells = [
+                Cell::from(throughput.name.clone()),
+                Cell::from(format!(
+                    "{}/s",
+                    settings.format_bytes(throughput.read_per_sec as u64)
+                )),
+                Cell::from(format!(
+                    "{}/s",
+                    settings.format_bytes(throughput.written_per_sec as u64)
+                )),
+                Cell::from(settings.format_bytes(throughput.total_read)),
+                Cell::from(settings.format_bytes(throughput.total_written)),
+                Cell::from(format!("{:.1}%", throughput.busy_percent)),
+            ];
+            Row::new(cells).height(1)
+        });
+
+        let table = Table::new(rows)
+            .header(table_header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Plain)
+                    .title("Disk I/O"),
+            )
+            .widths(&[
+                Constraint::Length(12),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Length(8),
+            ]);
+
+        frame.render_widget(table, area);
+    }
+
+    /// Takes the filesystem usage and the block device counters of a snapshot,
+    /// the throughput is the latest recorded one
+    pub fn update(&mut self, snapshot: &Snapshot, history: &MetricsHistory) {
+        self.filesystems = snapshot
+            .disks
+            .iter()
+            .map(|disk| Filesystem {
+                device: disk.device.clone(),
+                fs_type: disk.file_system.clone(),
+                mount_point: disk.mount_point.to_string_lossy().to_string(),
+                total_space: disk.total_space,
+                available_space: disk.available_space,
+                inodes: disk.inodes,
+            })
+            .collect();
+
+        let rate = |metric: Metric| history.latest(&metric).unwrap_or(0.0);
+        self.throughputs = snapshot
+            .disk_stats
+            .iter()
+            .filter(|stat| !stat.is_virtual())
+            .map(|stat| DiskThroughput {
+                name: stat.name.clone(),
+                read_per_sec: rate(Metric::DiskRead(stat.name.clone())),
+                written_per_sec: rate(Metric::DiskWritten(stat.name.clone())),
+                total_read: stat.bytes_read(),
+                total_written: stat.bytes_written(),
+                busy_percent: rate(Metric::DiskBusy(stat.name.clone())),
+            })
+            .collect();
+    }
+}
+
+impl<B: Backend> View<B> for StorageView {
+    /// Renders the usage of every mounted filesystem and the I/O throughput of the block devices
+    fn render(&mut self, frame: &mut Frame<B>, area: Rect, context: &ViewContext) {
+        // borders and the header take 2 + 1 lines
+        let filesystems_height = self.filesystems.len() as u16 + 3;
+        let storage_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(filesystems_height),
+                    Constraint::Min(self.throughputs.len() as u16 + 3),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        self.render_filesystems(frame, storage_layout[0], context.settings);
+        self.render_throughputs(frame, storage_layout[1], context.settings);
+    }
+}
+
+fn filesystem_row_layout(area: Rect) -> Vec<Rect> {
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(25),
+                Constraint::Length(8),
+                Constraint::Percentage(20),
+                Constraint::Percentage(35),
+                Constraint::Min(8),
+            ]
+            .as_ref(),
+        )
+        .horizontal_margin(1)
+        .split(area)
+}
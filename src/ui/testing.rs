@@ -0,0 +1,223 @@
+//! Fixtures and helpers for the rendering tests of the views.
+
+use super::{DisplaySettings, View, ViewContext};
+use crate::core::{
+    CpuFrequency, CpuSnapshot, CpuStat, DiskSnapshot, HostSnapshot, MemorySnapshot, MetricsHistory,
+    NetworkSnapshot, ProcessSnapshot, Snapshot,
+};
+use crate::keymap::Keymap;
+use std::path::PathBuf;
+use sysinfo::{Pid, PidExt};
+use tui::{backend::TestBackend, buffer::Buffer, Frame, Terminal};
+
+/// A machine with two cores, three processes and one network interface.
+/// The CPU times are the ones of `/proc/stat` right after boot.
+pub(crate) fn snapshot() -> Snapshot {
+    Snapshot {
+        host: HostSnapshot {
+            os: String::from("Heimdal Linux"),
+            os_version: String::from("1.0"),
+            kernel_version: String::from("6.1.0-test"),
+            host_name: String::from("testhost"),
+            uptime: 7200,
+            load_average: [1.5, 1.0, 0.5],
+        },
+        cpu: CpuSnapshot {
+            brand: String::from("Test CPU 3000"),
+            frequency: 3000,
+            physical_core_count: Some(2),
+            usage: 50.0,
+            core_usages: vec![40.0, 60.0],
+            temperatures: vec![
+                (String::from("Core 0"), 42.0),
+                (String::from("Core 1"), 44.0),
+            ],
+            stat: cpu_stat(0, 0, 0),
+            frequencies: vec![frequency(0, 3_000_000), frequency(1, 800_000)],
+        },
+        memory: MemorySnapshot {
+            total: 8_000_000,
+            used: 2_000_000,
+            available: 6_000_000,
+            ..MemorySnapshot::default()
+        },
+        disks: vec![DiskSnapshot {
+            device: String::from("/dev/sda1"),
+            file_system: String::from("ext4"),
+            mount_point: PathBuf::from("/"),
+            total_space: 100_000_000_000,
+            available_space: 40_000_000_000,
+            inodes: None,
+        }],
+        networks: vec![NetworkSnapshot {
+            name: String::from("eth0"),
+            state: Some(String::from("up")),
+            total_received: 2048,
+            total_transmitted: 1024,
+            ..NetworkSnapshot::default()
+        }],
+        processes: vec![
+            process(1, None, "init", "root", 0.5, 4_096),
+            process(42, Some(1), "editor", "alice", 12.5, 102_400),
+            process(7, Some(1), "backup", "bob", 3.0, 20_480),
+        ],
+        ..Snapshot::default()
+    }
+}
+
+/// CPU times of the machine with both cores spending the same time in each state
+pub(crate) fn cpu_stat(user: u64, system: u64, idle: u64) -> CpuStat {
+    let line = |name: &str, factor: u64| {
+        format!(
+            "{} {} 0 {} {} 0 0 0 0 0 0\n",
+            name,
+            user * factor,
+            system * factor,
+            idle * factor
+        )
+    };
+    CpuStat::parse(&(line("cpu", 2) + &line("cpu0", 1) + &line("cpu1", 1)))
+}
+
+fn frequency(cpu: usize, current: u64) -> CpuFrequency {
+    CpuFrequency {
+        cpu,
+        current: Some(current),
+        min: Some(800_000),
+        max: Some(4_000_000),
+        governor: Some(String::from("powersave")),
+        energy_performance_preference: Some(String::from("performance")),
+        core_throttle_count: Some(3),
+        package_throttle_count: Some(0),
+    }
+}
+
+fn process(
+    pid: u32,
+    parent: Option<u32>,
+    name: &str,
+    user: &str,
+    cpu_usage: f32,
+    memory: u64,
+) -> ProcessSnapshot {
+    ProcessSnapshot {
+        pid: Pid::from_u32(pid),
+        parent: parent.map(Pid::from_u32),
+        name: name.to_string(),
+        command: vec![format!("/usr/bin/{}", name), String::from("--test")],
+        executable: PathBuf::from(format!("/usr/bin/{}", name)),
+        working_directory: PathBuf::from("/"),
+        user: user.to_string(),
+        cpu_usage,
+        memory,
+        virtual_memory: memory * 4,
+        status: String::from("Sleeping"),
+        start_time: 0,
+        run_time: 3_600,
+    }
+}
+
+/// Draws a frame on a test terminal and returns its lines
+pub(crate) fn render<F>(width: u16, height: u16, draw: F) -> Vec<String>
+where
+    F: FnOnce(&mut Frame<TestBackend>),
+{
+    lines(&render_buffer(width, height, draw))
+}
+
+/// Draws a frame on a test terminal and returns its cells, for checking the styles
+pub(crate) fn render_buffer<F>(width: u16, height: u16, draw: F) -> Buffer
+where
+    F: FnOnce(&mut Frame<TestBackend>),
+{
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(draw).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+pub(crate) fn lines(buffer: &Buffer) -> Vec<String> {
+    buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|line| line.iter().map(|cell| cell.symbol.as_str()).collect())
+        .collect()
+}
+
+/// Renders a view over the whole test terminal with the default keys and returns its lines
+pub(crate) fn render_view(
+    width: u16,
+    height: u16,
+    view: &mut dyn View<TestBackend>,
+    snapshot: &Snapshot,
+    history: &MetricsHistory,
+) -> Vec<String> {
+    render_view_with_keymap(width, height, view, snapshot, history, &Keymap::default())
+}
+
+/// Renders a view like `render_view` and returns its cells
+pub(crate) fn render_view_buffer(
+    width: u16,
+    height: u16,
+    view: &mut dyn View<TestBackend>,
+    snapshot: &Snapshot,
+    history: &MetricsHistory,
+) -> Buffer {
+    let keymap = Keymap::default();
+    let context = ViewContext {
+        snapshot,
+        history,
+        keymap: &keymap,
+        settings: &DisplaySettings::DEFAULT,
+    };
+    render_buffer(width, height, |frame| {
+        view.render(frame, frame.size(), &context)
+    })
+}
+
+pub(crate) fn render_view_with_keymap(
+    width: u16,
+    height: u16,
+    view: &mut dyn View<TestBackend>,
+    snapshot: &Snapshot,
+    history: &MetricsHistory,
+    keymap: &Keymap,
+) -> Vec<String> {
+    let context = ViewContext {
+        snapshot,
+        history,
+        keymap,
+        settings: &DisplaySettings::DEFAULT,
+    };
+    render(width, height, |frame| {
+        view.render(frame, frame.size(), &context)
+    })
+}
+
+pub(crate) fn render_view_with_settings(
+    width: u16,
+    height: u16,
+    view: &mut dyn View<TestBackend>,
+    snapshot: &Snapshot,
+    history: &MetricsHistory,
+    settings: &DisplaySettings,
+) -> Vec<String> {
+    let keymap = Keymap::default();
+    let context = ViewContext {
+        snapshot,
+        history,
+        keymap: &keymap,
+        settings,
+    };
+    render(width, height, |frame| {
+        view.render(frame, frame.size(), &context)
+    })
+}
+
+/// Returns the index of the first line containing `text`,
+/// failing with the whole screen so a broken layout is easy to see
+pub(crate) fn find_line(lines: &[String], text: &str) -> usize {
+    lines
+        .iter()
+        .position(|line| line.contains(text))
+        .unwrap_or_else(|| panic!("{:?} not rendered in\n{}", text, lines.join("\n")))
+}
@@ -0,0 +1,276 @@
+use serde::{de, Deserialize, Deserializer};
+use tui::style::Color;
+
+/// Colours of every view. Colours are named (`lightred`), indexed in the 256 colour palette (`208`)
+/// or given in hex (`"#ff8700"`), the backend converts them to what the terminal supports.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Tab titles
+    #[serde(deserialize_with = "color")]
+    pub text: Color,
+    /// Active tab, hotkeys, filter matches and the selected pane of the process details
+    #[serde(deserialize_with = "color")]
+    pub highlight: Color,
+    /// Background of the table headers
+    #[serde(deserialize_with = "color")]
+    pub header: Color,
+    /// Text of the table headers
+    #[serde(deserialize_with = "color")]
+    pub header_text: Color,
+    /// Captions and the idle CPU time
+    #[serde(deserialize_with = "color")]
+    pub dim: Color,
+    #[serde(deserialize_with = "color")]
+    pub error: Color,
+    /// Gauges below the warning threshold
+    #[serde(deserialize_with = "color")]
+    pub normal: Color,
+    #[serde(deserialize_with = "color")]
+    pub warning: Color,
+    #[serde(deserialize_with = "color")]
+    pub high: Color,
+    #[serde(deserialize_with = "color")]
+    pub critical: Color,
+    /// Lines of the charts: the first one is RAM, CPU and received bytes, the second one cores
+    /// and sent bytes, the third one swap and full pressure, the fourth one discharge rate
+    /// and partial pressure
+    #[serde(deserialize_with = "colors")]
+    pub series: [Color; 4],
+    /// Shares of the CPU time: user, nice, system, iowait, irq, softirq, steal, guest and idle
+    #[serde(deserialize_with = "colors")]
+    pub cpu_states: [Color; 9],
+}
+
+impl Theme {
+    /// For terminals with a dark background, the default
+    pub const DARK: Theme = Theme {
+        text: Color::White,
+        highlight: Color::Yellow,
+        header: Color::Blue,
+        header_text: Color::Reset,
+        dim: Color::DarkGray,
+        error: Color::LightRed,
+        normal: Color::LightGreen,
+        warning: Color::Yellow,
+        high: Color::LightRed,
+        critical: Color::Red,
+        series: [
+            Color::LightGreen,
+            Color::LightBlue,
+            Color::LightRed,
+            Color::LightYellow,
+        ],
+        cpu_states: [
+            Color::LightGreen,
+            Color::Green,
+            Color::LightRed,
+            Color::LightMagenta,
+            Color::Yellow,
+            Color::LightYellow,
+            Color::LightCyan,
+            Color::LightBlue,
+            Color::DarkGray,
+        ],
+    };
+
+    /// For terminals with a light background
+    pub const LIGHT: Theme = Theme {
+        text: Color::Black,
+        highlight: Color::Blue,
+        header: Color::Rgb(0xc6, 0xdb, 0xef),
+        header_text: Color::Black,
+        dim: Color::Gray,
+        error: Color::Red,
+        normal: Color::Green,
+        warning: Color::Rgb(0xb5, 0x89, 0x00),
+        high: Color::Rgb(0xcb, 0x4b, 0x16),
+        critical: Color::Red,
+        series: [
+            Color::Green,
+            Color::Blue,
+            Color::Red,
+            Color::Rgb(0xb5, 0x89, 0x00),
+        ],
+        cpu_states: [
+            Color::Green,
+            Color::Cyan,
+            Color::Red,
+            Color::Magenta,
+            Color::Rgb(0xb5, 0x89, 0x00),
+            Color::Rgb(0xcb, 0x4b, 0x16),
+            Color::Blue,
+            Color::Rgb(0x6c, 0x71, 0xc4),
+            Color::Gray,
+        ],
+    };
+
+    /// Bright colours only, on a black background
+    pub const HIGH_CONTRAST: Theme = Theme {
+        text: Color::White,
+        highlight: Color::LightCyan,
+        header: Color::White,
+        header_text: Color::Black,
+        dim: Color::Gray,
+        error: Color::LightRed,
+        normal: Color::LightGreen,
+        warning: Color::LightYellow,
+        high: Color::LightMagenta,
+        critical: Color::LightRed,
+        series: [
+            Color::LightGreen,
+            Color::LightCyan,
+            Color::LightMagenta,
+            Color::LightYellow,
+        ],
+        cpu_states: [
+            Color::LightGreen,
+            Color::Green,
+            Color::LightRed,
+            Color::LightMagenta,
+            Color::LightYellow,
+            Color::Yellow,
+            Color::LightCyan,
+            Color::LightBlue,
+            Color::Gray,
+        ],
+    };
+
+    /// Names of the built-in themes
+    pub const BUILT_IN: [&'static str; 3] = ["dark", "light", "high-contrast"];
+
+    pub fn built_in(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::DARK),
+            "light" => Some(Theme::LIGHT),
+            "high-contrast" => Some(Theme::HIGH_CONTRAST),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
+
+/// Parses a colour name, a palette index or a hex colour
+fn parse_color(text: &str) -> Result<Color, String> {
+    if let Some(hex) = text.strip_prefix('#') {
+        let channel = |index: usize| {
+            hex.get(index..index + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+        };
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(red), Some(green), Some(blue)) => Ok(Color::Rgb(red, green, blue)),
+            _ => Err(format!("\"{}\" is not a #rrggbb colour", text)),
+        };
+    }
+    if let Ok(index) = text.parse() {
+        return Ok(Color::Indexed(index));
+    }
+
+    let name: String = text
+        .chars()
+        .filter(|ch| !matches!(ch, '-' | '_' | ' '))
+        .collect::<String>()
+        .to_ascii_lowercase();
+    Ok(match name.as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(format!("unknown colour \"{}\"", text)),
+    })
+}
+
+/// A colour of the theme files, palette indexes can be written as numbers
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorValue {
+    Index(u8),
+    Text(String),
+}
+
+impl ColorValue {
+    fn color<E: de::Error>(self) -> Result<Color, E> {
+        match self {
+            ColorValue::Index(index) => Ok(Color::Indexed(index)),
+            ColorValue::Text(text) => parse_color(&text).map_err(E::custom),
+        }
+    }
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    ColorValue::deserialize(deserializer)?.color()
+}
+
+fn colors<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[Color; N], D::Error> {
+    let values = Vec::<ColorValue>::deserialize(deserializer)?;
+    if values.len() != N {
+        return Err(de::Error::invalid_length(
+            values.len(),
+            &format!("{} colours", N).as_str(),
+        ));
+    }
+    let mut colors = [Color::Reset; N];
+    for (color, value) in colors.iter_mut().zip(values) {
+        *color = value.color()?;
+    }
+    Ok(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Theme;
+    use tui::style::Color;
+
+    #[test]
+    fn parses_a_theme_file_over_the_dark_theme() {
+        let theme: Theme = toml::from_str(
+            r##"
+            highlight = "#ff8700"
+            header = 24
+            warning = "light-red"
+            series = ["green", "blue", "red", "Light_Yellow"]
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(theme.highlight, Color::Rgb(0xff, 0x87, 0x00));
+        assert_eq!(theme.header, Color::Indexed(24));
+        assert_eq!(theme.warning, Color::LightRed);
+        assert_eq!(theme.series[3], Color::LightYellow);
+        assert_eq!(theme.text, Theme::DARK.text);
+        assert_eq!(theme.cpu_states, Theme::DARK.cpu_states);
+    }
+
+    #[test]
+    fn rejects_invalid_colours() {
+        let error = |text: &str| match toml::from_str::<Theme>(text) {
+            Err(error) => error.message().to_string(),
+            Ok(_) => panic!("{:?} was accepted", text),
+        };
+
+        assert!(error("text = \"pink\"").contains("unknown colour \"pink\""));
+        assert!(error("text = \"#12345\"").contains("not a #rrggbb colour"));
+        assert!(error("series = [\"red\"]").contains("expected 4 colours"));
+        assert!(error("background = \"red\"").contains("unknown field `background`"));
+    }
+}